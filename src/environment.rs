@@ -7,7 +7,8 @@ use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub(crate) struct Environment {
-    values: HashMap<String, Value>, // 变量名到值的映射
+    values: HashMap<String, Value>,  // 变量名到值的映射
+    constants: HashMap<String, i32>, // 常量名到声明所在行的映射
     enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
@@ -15,6 +16,7 @@ impl Environment {
     pub(crate) fn new() -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Environment {
             values: HashMap::new(),
+            constants: HashMap::new(),
             enclosing: None,
         }))
     }
@@ -22,16 +24,55 @@ impl Environment {
     pub(crate) fn new_enclosing(enclosing: Rc<RefCell<Environment>>) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Environment {
             values: HashMap::new(),
+            constants: HashMap::new(),
             enclosing: Some(enclosing),
         }))
     }
 
-    pub(crate) fn define(&mut self, name: String, value: Value) {
+    // 环境链最外层的环境，即当前模块（或主脚本）的全局作用域
+    pub(crate) fn global(environment: &Rc<RefCell<Environment>>) -> Rc<RefCell<Environment>> {
+        let mut environment = environment.clone();
+        loop {
+            let enclosing = environment.borrow().enclosing.clone();
+            match enclosing {
+                Some(enclosing) => environment = enclosing,
+                None => return environment,
+            }
+        }
+    }
+
+    // 同一环境中可以重新定义变量，但不能用新的声明覆盖常量
+    pub(crate) fn define(&mut self, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        self.check_redefinable(name)?;
+        self.values.insert(name.lexeme.clone(), value);
+        Ok(())
+    }
+
+    pub(crate) fn define_constant(
+        &mut self,
+        name: &Token,
+        value: Value,
+        line: i32,
+    ) -> Result<(), RuntimeError> {
+        self.check_redefinable(name)?;
+        self.constants.insert(name.lexeme.clone(), line);
+        self.values.insert(name.lexeme.clone(), value);
+        Ok(())
+    }
+
+    // 解释器自己引入的名字（this、super、原生函数等）直接绑定，它们所在的环境中没有常量
+    pub(crate) fn bind(&mut self, name: String, value: Value) {
         self.values.insert(name, value);
     }
 
+    // 当前环境中的常量声明所在的行，变量或未定义的名字返回 None
+    pub(crate) fn constant_line(&self, name: &str) -> Option<i32> {
+        self.constants.get(name).copied()
+    }
+
     pub(crate) fn assign(&mut self, name: &Token, value: Value) -> Result<(), RuntimeError> {
         if self.values.contains_key(&name.lexeme) {
+            self.check_mutable(name)?;
             self.values.insert(name.lexeme.clone(), value);
             return Ok(());
         }
@@ -41,10 +82,10 @@ impl Environment {
             return Ok(());
         }
 
-        Err(RuntimeError {
-            token: name.clone(),
-            message: format!("Undefined variable '{}'.", &name.lexeme),
-        })
+        Err(RuntimeError::new(
+            name.clone(),
+            format!("Undefined variable '{}'.", &name.lexeme),
+        ))
     }
 
     pub(crate) fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
@@ -54,9 +95,80 @@ impl Environment {
         if let Some(enclosing) = &self.enclosing {
             return enclosing.borrow().get(name);
         }
-        Err(RuntimeError {
-            token: name.clone(),
-            message: format!("Undefined variable '{}'.", &name.lexeme),
-        })
+        Err(RuntimeError::new(
+            name.clone(),
+            format!("Undefined variable '{}'.", &name.lexeme),
+        ))
+    }
+
+    pub(crate) fn get_at(&self, distance: usize, name: &Token) -> Result<Value, RuntimeError> {
+        if distance == 0 {
+            return self.values.get(&name.lexeme).cloned().ok_or_else(|| {
+                RuntimeError::new(
+                    name.clone(),
+                    format!("Undefined variable '{}'.", &name.lexeme),
+                )
+            });
+        }
+        self.ancestor(distance).borrow().get_at(0, name)
+    }
+
+    pub(crate) fn assign_at(
+        &mut self,
+        distance: usize,
+        name: &Token,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        if distance == 0 {
+            self.check_mutable(name)?;
+            self.values.insert(name.lexeme.clone(), value);
+            return Ok(());
+        }
+        self.ancestor(distance)
+            .borrow_mut()
+            .assign_at(0, name, value)
+    }
+
+    fn check_mutable(&self, name: &Token) -> Result<(), RuntimeError> {
+        match self.constants.get(&name.lexeme) {
+            Some(line) => Err(RuntimeError::new(
+                name.clone(),
+                format!(
+                    "Can't assign to constant '{}' declared on line {}.",
+                    name.lexeme, line
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn check_redefinable(&self, name: &Token) -> Result<(), RuntimeError> {
+        match self.constants.get(&name.lexeme) {
+            Some(line) => Err(RuntimeError::new(
+                name.clone(),
+                format!(
+                    "Can't redeclare constant '{}' declared on line {}.",
+                    name.lexeme, line
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // 沿 enclosing 链向外走 distance 层（distance 至少为 1）
+    fn ancestor(&self, distance: usize) -> Rc<RefCell<Environment>> {
+        let mut environment = self
+            .enclosing
+            .clone()
+            .expect("Resolver produced a scope depth deeper than the environment chain.");
+        for _ in 1..distance {
+            let enclosing = environment
+                .borrow()
+                .enclosing
+                .clone()
+                .expect("Resolver produced a scope depth deeper than the environment chain.");
+            environment = enclosing;
+        }
+        environment
     }
 }
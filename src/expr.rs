@@ -1,13 +1,54 @@
+use crate::stmt::FunctionDecl;
 use crate::token::Token;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// 为需要解析作用域的表达式分配唯一编号，解释器用它查找 Resolver 记录的作用域深度
+pub(crate) fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
 
 pub(crate) trait Visitor<R> {
     fn visit_binary_expr(&mut self, left: &Expr, operator: &Token, right: &Expr) -> R;
     fn visit_grouping_expr(&mut self, expression: &Expr) -> R;
     fn visit_literal_expr(&mut self, value: &crate::token::Literal) -> R;
     fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> R;
-    fn visit_variable_expr(&mut self, name: &Token) -> R;
-    fn visit_assign_expr(&mut self, name: &Token, value: &Expr) -> R;
+    fn visit_variable_expr(&mut self, id: usize, name: &Token) -> R;
+    fn visit_assign_expr(&mut self, id: usize, name: &Token, value: &Expr) -> R;
     fn visit_logical_expr(&mut self, left: &Expr, operator: &Token, right: &Expr) -> R;
+    fn visit_call_expr(&mut self, callee: &Expr, paren: &Token, arguments: &[Argument]) -> R;
+    fn visit_get_expr(&mut self, object: &Expr, name: &Token) -> R;
+    fn visit_set_expr(&mut self, object: &Expr, name: &Token, value: &Expr) -> R;
+    fn visit_this_expr(&mut self, id: usize, keyword: &Token) -> R;
+    fn visit_super_expr(&mut self, id: usize, keyword: &Token, method: &Token) -> R;
+    fn visit_function_expr(&mut self, declaration: &Rc<FunctionDecl>) -> R;
+    fn visit_list_expr(&mut self, elements: &[Expr]) -> R;
+    fn visit_index_expr(&mut self, object: &Expr, bracket: &Token, index: &Expr) -> R;
+    fn visit_index_set_expr(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> R;
+    fn visit_map_expr(&mut self, brace: &Token, entries: &[(Expr, Expr)]) -> R;
+    fn visit_interpolation_expr(&mut self, token: &Token, parts: &[Expr]) -> R;
+    fn visit_compound_assign_expr(&mut self, target: &Expr, operator: &Token, value: &Expr) -> R;
+    fn visit_increment_expr(&mut self, target: &Expr, operator: &Token, postfix: bool) -> R;
+    fn visit_destructure_assign_expr(
+        &mut self,
+        pattern: &AssignPattern,
+        equals: &Token,
+        value: &Expr,
+    ) -> R;
+    fn visit_conditional_expr(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Expr,
+        else_branch: &Expr,
+    ) -> R;
 }
 
 #[derive(Debug)]
@@ -28,9 +69,11 @@ pub(crate) enum Expr {
         right: Box<Expr>,
     },
     Variable {
+        id: usize,
         name: Token,
     },
     Assign {
+        id: usize,
         name: Token,
         value: Box<Expr>,
     },
@@ -39,9 +82,133 @@ pub(crate) enum Expr {
         operator: Token,
         right: Box<Expr>,
     },
+    Call {
+        callee: Box<Expr>,
+        paren: Token,
+        arguments: Vec<Argument>,
+    },
+    Get {
+        object: Box<Expr>,
+        name: Token,
+    },
+    Set {
+        object: Box<Expr>,
+        name: Token,
+        value: Box<Expr>,
+    },
+    This {
+        id: usize,
+        keyword: Token,
+    },
+    Super {
+        id: usize,
+        keyword: Token,
+        method: Token,
+    },
+    Function {
+        declaration: Rc<FunctionDecl>,
+    },
+    List {
+        elements: Vec<Expr>,
+    },
+    Index {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+    },
+    IndexSet {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+    Map {
+        brace: Token,
+        entries: Vec<(Expr, Expr)>,
+    },
+    // 插值字符串的各个部分，求值后转成字符串依次拼接
+    Interpolation {
+        token: Token,
+        parts: Vec<Expr>,
+    },
+    // target 是 Variable、Get 或 Index
+    CompoundAssign {
+        target: Box<Expr>,
+        operator: Token,
+        value: Box<Expr>,
+    },
+    Increment {
+        target: Box<Expr>,
+        operator: Token,
+        postfix: bool,
+    },
+    Conditional {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
+    // [a, b] = e 或 {x, y} = e
+    DestructureAssign {
+        pattern: AssignPattern,
+        equals: Token,
+        value: Box<Expr>,
+    },
+}
+
+// 调用处的实参：位置参数、name: value 形式的命名参数，以及 ...list 展开的参数
+#[derive(Debug)]
+pub(crate) enum Argument {
+    Positional { value: Expr },
+    Named { name: Token, value: Expr },
+    Spread { ellipsis: Token, value: Expr },
+}
+
+impl Argument {
+    pub(crate) fn value(&self) -> &Expr {
+        match self {
+            Argument::Positional { value }
+            | Argument::Named { value, .. }
+            | Argument::Spread { value, .. } => value,
+        }
+    }
+}
+
+// 解构赋值的左侧，与解构声明的 Destructure 对应
+#[derive(Debug)]
+pub(crate) enum AssignPattern {
+    // 各个 target 和 rest 都是可赋值的表达式
+    List {
+        bracket: Token,
+        targets: Vec<Expr>,
+        rest: Option<Box<Expr>>,
+    },
+    // 属性名同时是被赋值的变量，names 总是 Expr::Variable
+    Object {
+        brace: Token,
+        names: Vec<Expr>,
+    },
+}
+
+impl AssignPattern {
+    // 按赋值的顺序列出所有 target，[a, ...b] 中的 b 在最后
+    pub(crate) fn targets(&self) -> Vec<&Expr> {
+        match self {
+            AssignPattern::List { targets, rest, .. } => {
+                targets.iter().chain(rest.as_deref()).collect()
+            }
+            AssignPattern::Object { names, .. } => names.iter().collect(),
+        }
+    }
 }
 
 impl Expr {
+    pub(crate) fn is_assignable(&self) -> bool {
+        matches!(
+            self,
+            Expr::Variable { .. } | Expr::Get { .. } | Expr::Index { .. }
+        )
+    }
+
     pub(crate) fn accept<R>(&self, visitor: &mut impl Visitor<R>) -> R {
         match self {
             Expr::Binary {
@@ -52,13 +219,65 @@ impl Expr {
             Expr::Grouping { expression } => visitor.visit_grouping_expr(expression),
             Expr::Literal { value } => visitor.visit_literal_expr(value),
             Expr::Unary { operator, right } => visitor.visit_unary_expr(operator, right),
-            Expr::Variable { name } => visitor.visit_variable_expr(name),
-            Expr::Assign { name, value } => visitor.visit_assign_expr(name, value),
+            Expr::Variable { id, name } => visitor.visit_variable_expr(*id, name),
+            Expr::Assign { id, name, value } => visitor.visit_assign_expr(*id, name, value),
             Expr::Logical {
                 left,
                 operator,
                 right,
             } => visitor.visit_logical_expr(left, operator, right),
+            Expr::Call {
+                callee,
+                paren,
+                arguments,
+            } => visitor.visit_call_expr(callee, paren, arguments),
+            Expr::Get { object, name } => visitor.visit_get_expr(object, name),
+            Expr::Set {
+                object,
+                name,
+                value,
+            } => visitor.visit_set_expr(object, name, value),
+            Expr::This { id, keyword } => visitor.visit_this_expr(*id, keyword),
+            Expr::Super {
+                id,
+                keyword,
+                method,
+            } => visitor.visit_super_expr(*id, keyword, method),
+            Expr::Function { declaration } => visitor.visit_function_expr(declaration),
+            Expr::List { elements } => visitor.visit_list_expr(elements),
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => visitor.visit_index_expr(object, bracket, index),
+            Expr::IndexSet {
+                object,
+                bracket,
+                index,
+                value,
+            } => visitor.visit_index_set_expr(object, bracket, index, value),
+            Expr::Map { brace, entries } => visitor.visit_map_expr(brace, entries),
+            Expr::Interpolation { token, parts } => visitor.visit_interpolation_expr(token, parts),
+            Expr::CompoundAssign {
+                target,
+                operator,
+                value,
+            } => visitor.visit_compound_assign_expr(target, operator, value),
+            Expr::Increment {
+                target,
+                operator,
+                postfix,
+            } => visitor.visit_increment_expr(target, operator, *postfix),
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => visitor.visit_conditional_expr(condition, then_branch, else_branch),
+            Expr::DestructureAssign {
+                pattern,
+                equals,
+                value,
+            } => visitor.visit_destructure_assign_expr(pattern, equals, value),
         }
     }
 }
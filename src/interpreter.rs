@@ -1,46 +1,134 @@
 use crate::environment::Environment;
-use crate::expr::Expr;
+use crate::expr::{Argument, AssignPattern, Expr};
+use crate::lox_callable::LoxCallable;
+use crate::lox_class::{ClassMembers, LoxClass, Property};
+use crate::lox_enum::LoxEnum;
+use crate::lox_function::LoxFunction;
+use crate::lox_instance::LoxInstance;
+use crate::lox_map::{LoxMap, MapKey};
+use crate::lox_module::LoxModule;
+use crate::lox_trait::LoxTrait;
+use crate::native_function;
+use crate::parser::Parser;
+use crate::resolver::Resolver;
 use crate::runtime_error::RuntimeError;
-use crate::stmt::Stmt;
+use crate::scanner::Scanner;
+use crate::stmt::{CatchClause, ClassBody, Destructure, FunctionDecl, MatchArm, Pattern, Stmt};
 use crate::token::{Literal, Token};
 use crate::token_type::TokenType;
-use crate::value::Value;
+use crate::unwind::Unwind;
 use crate::value::Value::*;
+use crate::value::{Arithmetic, Value};
 use crate::Lox;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
+// 函数调用的最大嵌套深度，超过时报告运行时错误，而不是让解释器自身的栈溢出
+const MAX_CALL_DEPTH: usize = 1000;
+
 pub(crate) struct Interpreter {
+    // 内置错误被 catch 捕获时包装成这个类的实例
+    error_class: Rc<LoxClass>,
     environment: Rc<RefCell<Environment>>,
+    // 表达式编号到作用域深度的映射，由 Resolver 填充；不在其中的变量是全局变量
+    locals: HashMap<usize, usize>,
+    // 正在执行的脚本或模块文件，import 的相对路径以它所在的目录为基准
+    script_path: Option<PathBuf>,
+    // 已经执行过的模块，按规范化后的路径缓存
+    modules: HashMap<PathBuf, Rc<LoxModule>>,
+    // 正在加载的模块链，用于检测循环导入
+    loading: Vec<PathBuf>,
+    // 正在执行的 Lox 函数调用层数
+    call_depth: usize,
 }
 
 impl Interpreter {
     pub(crate) fn new() -> Self {
+        let error_class = Rc::new(LoxClass::new(
+            "Error".to_string(),
+            None,
+            ClassMembers::default(),
+        ));
         Interpreter {
-            environment: Environment::new(),
+            environment: Self::new_globals(&error_class),
+            error_class,
+            locals: HashMap::new(),
+            script_path: None,
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            call_depth: 0,
+        }
+    }
+
+    // 每个模块都有自己的全局环境，其中预先定义了原生函数和内置类
+    fn new_globals(error_class: &Rc<LoxClass>) -> Rc<RefCell<Environment>> {
+        let globals = Environment::new();
+        native_function::define_natives(&mut globals.borrow_mut());
+        globals
+            .borrow_mut()
+            .bind("Error".to_string(), Class(error_class.clone()));
+        globals
+    }
+
+    pub(crate) fn set_script_path(&mut self, path: PathBuf) {
+        // 主脚本本身也算作正在加载，模块导入它时同样是循环导入
+        if let Ok(canonical) = path.canonicalize() {
+            self.loading = vec![canonical];
         }
+        self.script_path = Some(path);
     }
+
     pub(crate) fn interpret(&mut self, statements: Vec<Stmt>) {
         for i in statements {
-            if let Err(e) = self.execute(&i) {
-                Lox::runtime_error(e);
-                return;
+            match self.execute(&i) {
+                Ok(()) => {}
+                Err(Unwind::Error(e)) => {
+                    Lox::runtime_error(e);
+                    return;
+                }
+                // Resolver 已经拒绝了顶层的 return，Parser 已经拒绝了循环外的 break/continue
+                Err(_) => unreachable!("Control flow escaped to the top level."),
             }
         }
     }
+    pub(crate) fn resolve(&mut self, id: usize, depth: usize) {
+        self.locals.insert(id, depth);
+    }
+
+    fn look_up_variable(&self, id: usize, name: &Token) -> Result<Value, RuntimeError> {
+        match self.locals.get(&id) {
+            Some(distance) => self.environment.borrow().get_at(*distance, name),
+            None => Environment::global(&self.environment).borrow().get(name),
+        }
+    }
+
     fn evaluate(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
         expr.accept(self)
     }
 
-    fn execute(&mut self, stmt: &Stmt) -> Result<(), RuntimeError> {
+    // 在给定的环境中求值，用于参数的默认值
+    pub(crate) fn evaluate_in(
+        &mut self,
+        expr: &Expr,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Value, RuntimeError> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let result = self.evaluate(expr);
+        self.environment = previous;
+        result
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> Result<(), Unwind> {
         stmt.accept(self)
     }
 
-    fn execute_block(
+    pub(crate) fn execute_block(
         &mut self,
-        statements: &Vec<Stmt>,
+        statements: &[Stmt],
         environment: Rc<RefCell<Environment>>,
-    ) -> Result<(), RuntimeError> {
+    ) -> Result<(), Unwind> {
         let previous = self.environment.clone();
         self.environment = environment;
         for stmt in statements {
@@ -53,60 +141,139 @@ impl Interpreter {
         Ok(())
     }
 
-    fn check_number_operand(operator: &Token, operand: &Value) -> Result<(), RuntimeError> {
-        if let Number(_) = operand {
-            return Ok(());
+    pub(crate) fn enter_call(&mut self, paren: &Token) -> Result<(), RuntimeError> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(
+                paren.clone(),
+                "Stack overflow.".to_string(),
+            ));
         }
-        Err(RuntimeError::new(
-            operator.clone(),
-            "Operand must be a number.".to_string(),
-        ))
+        self.call_depth += 1;
+        Ok(())
     }
 
-    fn check_number_operands(
-        operator: &Token,
-        left: &Value,
-        right: &Value,
-    ) -> Result<(), RuntimeError> {
-        if let (Number(_), Number(_)) = (left, right) {
-            return Ok(());
+    pub(crate) fn exit_call(&mut self) {
+        self.call_depth -= 1;
+    }
+
+    // 加载并执行模块，同一个文件只执行一次
+    fn load_module(&mut self, path_token: &Token) -> Result<Rc<LoxModule>, RuntimeError> {
+        let Some(Literal::String(relative)) = &path_token.literal else {
+            unreachable!("Import path is always a string literal.")
+        };
+        let base = self
+            .script_path
+            .as_deref()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new(""));
+        let path = base.join(relative).canonicalize().map_err(|e| {
+            RuntimeError::new(
+                path_token.clone(),
+                format!("Could not find module '{}': {}.", relative, e),
+            )
+        })?;
+
+        if let Some(module) = self.modules.get(&path) {
+            return Ok(module.clone());
         }
-        Err(RuntimeError::new(
-            operator.clone(),
-            "Operands must be numbers.".to_string(),
-        ))
+        if let Some(start) = self.loading.iter().position(|loading| *loading == path) {
+            let cycle: Vec<_> = self.loading[start..]
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(RuntimeError::new(
+                path_token.clone(),
+                format!("Import cycle detected: {}.", cycle.join(" -> ")),
+            ));
+        }
+
+        let source = std::fs::read_to_string(&path).map_err(|e| {
+            RuntimeError::new(
+                path_token.clone(),
+                format!("Could not read module '{}': {}.", relative, e),
+            )
+        })?;
+        let statements = Parser::new(Scanner::new(source).scan_tokens()).parse();
+        if !Lox::had_error() {
+            Resolver::new(self).resolve(&statements);
+        }
+        if Lox::had_error() {
+            return Err(RuntimeError::new(
+                path_token.clone(),
+                format!("Could not compile module '{}'.", relative),
+            ));
+        }
+
+        let environment = Self::new_globals(&self.error_class);
+        let previous_environment = std::mem::replace(&mut self.environment, environment.clone());
+        let previous_path = self.script_path.replace(path.clone());
+        self.loading.push(path.clone());
+        let result = statements.iter().try_for_each(|stmt| self.execute(stmt));
+        self.loading.pop();
+        self.script_path = previous_path;
+        self.environment = previous_environment;
+        match result {
+            Ok(()) => {}
+            Err(Unwind::Error(e)) => return Err(e),
+            Err(_) => unreachable!("Control flow escaped to the top level of a module."),
+        }
+
+        let name = path
+            .file_stem()
+            .map_or(relative.clone(), |stem| stem.to_string_lossy().to_string());
+        let exports = statements
+            .iter()
+            .flat_map(|stmt| stmt.declared_names())
+            .map(|name| name.lexeme.clone())
+            .collect();
+        let module = Rc::new(LoxModule::new(name, environment, exports));
+        self.modules.insert(path, module.clone());
+        Ok(module)
     }
-}
 
-impl crate::expr::Visitor<Result<Value, RuntimeError>> for Interpreter {
-    fn visit_binary_expr(
+    // 二元运算的求值，复合赋值也通过它复用同样的类型检查；operator 只用于报告错误的位置
+    fn binary_operation(
         &mut self,
-        left: &Expr,
         operator: &Token,
-        right: &Expr,
+        operator_type: TokenType,
+        left_value: Value,
+        right_value: Value,
     ) -> Result<Value, RuntimeError> {
-        let left_value = self.evaluate(left)?;
-        let right_value = self.evaluate(right)?;
-
-        match operator.token_type {
-            TokenType::MINUS => {
-                Self::check_number_operands(operator, &left_value, &right_value)?;
-                Ok(left_value - right_value)
+        if let Instance(instance) = &left_value {
+            if let Some(result) =
+                self.overloaded_operation(operator, operator_type, instance, &right_value)?
+            {
+                return Ok(result);
             }
+        }
+        match operator_type {
+            // 逗号运算符：左侧只为副作用求值
+            TokenType::COMMA => Ok(right_value),
             TokenType::PLUS => match (&left_value, &right_value) {
-                (Number(_), Number(_)) | (String(_), String(_)) => Ok(left_value + right_value),
+                (String(l), String(r)) => Ok(String(format!("{}{}", l, r))),
+                _ if left_value.is_number() && right_value.is_number() => {
+                    Self::arithmetic(operator, Arithmetic::Add, &left_value, &right_value)
+                }
                 _ => Err(RuntimeError::new(
                     operator.clone(),
                     "Operands must be two numbers or two strings.".to_string(),
                 )),
             },
-            TokenType::SLASH => {
-                Self::check_number_operands(operator, &left_value, &right_value)?;
-                Ok(left_value / right_value)
+            TokenType::MINUS => {
+                Self::arithmetic(operator, Arithmetic::Subtract, &left_value, &right_value)
             }
             TokenType::STAR => {
-                Self::check_number_operands(operator, &left_value, &right_value)?;
-                Ok(left_value * right_value)
+                Self::arithmetic(operator, Arithmetic::Multiply, &left_value, &right_value)
+            }
+            TokenType::SLASH => {
+                Self::arithmetic(operator, Arithmetic::Divide, &left_value, &right_value)
+            }
+            TokenType::TILDE_SLASH => {
+                Self::arithmetic(operator, Arithmetic::FloorDivide, &left_value, &right_value)
+            }
+            TokenType::PERCENT => {
+                Self::arithmetic(operator, Arithmetic::Remainder, &left_value, &right_value)
             }
             TokenType::GREATER => {
                 Self::check_number_operands(operator, &left_value, &right_value)?;
@@ -124,16 +291,694 @@ impl crate::expr::Visitor<Result<Value, RuntimeError>> for Interpreter {
                 Self::check_number_operands(operator, &left_value, &right_value)?;
                 Ok(Boolean(left_value <= right_value))
             }
-            TokenType::BANG_EQUAL => {
-                Self::check_number_operands(operator, &left_value, &right_value)?;
-                Ok(Boolean(left_value != right_value))
+            // 相等比较适用于任意类型，enum 值按结构比较
+            TokenType::BANG_EQUAL => Ok(Boolean(left_value != right_value)),
+            TokenType::EQUAL_EQUAL => Ok(Boolean(left_value == right_value)),
+            _ => unreachable!("Invalid binary operator"),
+        }
+    }
+
+    fn assign_variable(
+        &mut self,
+        id: usize,
+        name: &Token,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        match self.locals.get(&id) {
+            Some(distance) => self
+                .environment
+                .borrow_mut()
+                .assign_at(*distance, name, value),
+            None => Environment::global(&self.environment)
+                .borrow_mut()
+                .assign(name, value),
+        }
+    }
+
+    // 把值写入可赋值的目标，用于解构赋值
+    fn assign_target(&mut self, target: &Expr, value: Value) -> Result<(), RuntimeError> {
+        match target {
+            Expr::Variable { id, name } => self.assign_variable(*id, name, value),
+            Expr::Get { object, name } => {
+                let Instance(instance) = self.evaluate(object)? else {
+                    return Err(RuntimeError::new(
+                        name.clone(),
+                        "Only instances have fields.".to_string(),
+                    ));
+                };
+                self.set_property(&instance, name, value)
             }
-            TokenType::EQUAL_EQUAL => {
-                Self::check_number_operands(operator, &left_value, &right_value)?;
-                Ok(Boolean(left_value == right_value))
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                self.index_set(bracket, &object, &index, value)
             }
-            _ => unreachable!("Invalid binary operator"),
+            _ => unreachable!("Parser only accepts assignable targets."),
+        }
+    }
+
+    // 按解构模式取出各个变量的值，顺序与 Destructure::names 一致
+    fn destructure(
+        &mut self,
+        pattern: &Destructure,
+        value: &Value,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        match pattern {
+            Destructure::List {
+                bracket,
+                names,
+                rest,
+            } => Self::unpack_list(bracket, value, names.len(), rest.is_some()),
+            Destructure::Object { brace, names } => {
+                self.unpack_object(brace, &names.iter().collect::<Vec<_>>(), value)
+            }
+        }
+    }
+
+    // 按名字依次取出 map 的键、实例的属性或模块的导出
+    fn unpack_object(
+        &mut self,
+        brace: &Token,
+        names: &[&Token],
+        value: &Value,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        names
+            .iter()
+            .map(|&name| match value {
+                Map(map) => map
+                    .borrow()
+                    .get(&MapKey::String(name.lexeme.clone()))
+                    .ok_or_else(|| {
+                        RuntimeError::new(
+                            name.clone(),
+                            format!("Map has no key '{}'.", name.lexeme),
+                        )
+                    }),
+                Instance(instance) => self.get_property(instance, name),
+                Module(module) => module.get(name),
+                _ => Err(RuntimeError::new(
+                    brace.clone(),
+                    "Only maps, instances and modules can be destructured with '{}'.".to_string(),
+                )),
+            })
+            .collect()
+    }
+
+    // 取出列表的前 count 个元素；has_rest 时剩余的元素作为一个新列表放在最后
+    fn unpack_list(
+        token: &Token,
+        value: &Value,
+        count: usize,
+        has_rest: bool,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let List(elements) = value else {
+            return Err(RuntimeError::new(
+                token.clone(),
+                "Only lists can be destructured with '[]'.".to_string(),
+            ));
+        };
+        let elements = elements.borrow();
+        if elements.len() < count || (!has_rest && elements.len() > count) {
+            return Err(RuntimeError::new(
+                token.clone(),
+                format!(
+                    "Expected {}{} elements but got {}.",
+                    if has_rest { "at least " } else { "" },
+                    count,
+                    elements.len()
+                ),
+            ));
+        }
+        let mut values = elements[..count].to_vec();
+        if has_rest {
+            let rest = elements[count..].to_vec();
+            values.push(List(Rc::new(RefCell::new(rest))));
+        }
+        Ok(values)
+    }
+
+    // 读取可赋值的目标，用 update 计算新值后写回，返回 (旧值, 新值)；目标的子表达式只求值一次
+    fn update_target(
+        &mut self,
+        target: &Expr,
+        update: impl FnOnce(&mut Self, Value) -> Result<Value, RuntimeError>,
+    ) -> Result<(Value, Value), RuntimeError> {
+        match target {
+            Expr::Variable { id, name } => {
+                let old = self.look_up_variable(*id, name)?;
+                let new = update(self, old.clone())?;
+                self.assign_variable(*id, name, new.clone())?;
+                Ok((old, new))
+            }
+            Expr::Get { object, name } => {
+                let Instance(instance) = self.evaluate(object)? else {
+                    return Err(RuntimeError::new(
+                        name.clone(),
+                        "Only instances have fields.".to_string(),
+                    ));
+                };
+                let old = self.get_property(&instance, name)?;
+                let new = update(self, old.clone())?;
+                self.set_property(&instance, name, new.clone())?;
+                Ok((old, new))
+            }
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                let old = self.index_get(bracket, &object, &index)?;
+                let new = update(self, old.clone())?;
+                self.index_set(bracket, &object, &index, new.clone())?;
+                Ok((old, new))
+            }
+            _ => unreachable!("Parser only accepts assignable targets."),
+        }
+    }
+
+    // throw 抛出的值原样交给 catch；解释器自身的错误包装成带 message 和 line 字段的 Error 实例
+    fn error_value(&self, error: RuntimeError) -> Value {
+        if let Some(value) = error.value {
+            return value;
+        }
+        let mut instance = LoxInstance::new(self.error_class.clone());
+        let message = Token::new(TokenType::IDENTIFIER, "message".to_string(), None, 0);
+        let line = Token::new(TokenType::IDENTIFIER, "line".to_string(), None, 0);
+        instance.set(&message, String(error.message));
+        instance.set(&line, Int(error.token.line as i64));
+        Instance(Rc::new(RefCell::new(instance)))
+    }
+
+    // 没有标签的 break/continue 作用于最内层循环，否则作用于标签相同的循环
+    fn targets_loop(target: Option<&str>, label: Option<&Token>) -> bool {
+        match target {
+            None => true,
+            Some(target) => label.is_some_and(|label| label.lexeme == target),
+        }
+    }
+
+    // 执行 for-in 的一次迭代：循环变量绑定在新环境中，闭包捕获的是各自那一次的值。
+    // 返回 false 表示循环被 break 结束
+    fn iterate(
+        &mut self,
+        name: &Token,
+        value: Value,
+        body: &Stmt,
+        label: Option<&Token>,
+    ) -> Result<bool, Unwind> {
+        let environment = Environment::new_enclosing(self.environment.clone());
+        environment.borrow_mut().define(name, value)?;
+        match self.execute_block(std::slice::from_ref(body), environment) {
+            Ok(()) => Ok(true),
+            Err(Unwind::Break(target)) if Self::targets_loop(target.as_deref(), label) => Ok(false),
+            Err(Unwind::Continue(target)) if Self::targets_loop(target.as_deref(), label) => {
+                Ok(true)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // 为类或 trait 的各类成员创建函数，闭包是当前环境
+    fn class_members(&self, body: &ClassBody) -> ClassMembers {
+        let functions = |declarations: &[Rc<FunctionDecl>], allow_initializer: bool| {
+            declarations
+                .iter()
+                .map(|declaration| {
+                    let is_initializer = allow_initializer && declaration.name.lexeme == "init";
+                    let function = LoxFunction::new(
+                        declaration.clone(),
+                        self.environment.clone(),
+                        is_initializer,
+                    );
+                    (declaration.name.lexeme.clone(), Rc::new(function))
+                })
+                .collect()
+        };
+        ClassMembers {
+            methods: functions(&body.methods, true),
+            getters: functions(&body.getters, false),
+            setters: functions(&body.setters, false),
+            class_methods: functions(&body.class_methods, false),
+        }
+    }
+
+    // 读取实例的属性：字段优先，其次沿继承链逐层查找 getter 或方法
+    fn get_property(
+        &mut self,
+        instance: &Rc<RefCell<LoxInstance>>,
+        name: &Token,
+    ) -> Result<Value, RuntimeError> {
+        if let Some(value) = instance.borrow().field(&name.lexeme) {
+            return Ok(value);
+        }
+        let property = instance.borrow().class().find_property(&name.lexeme);
+        self.property_value(property, Instance(instance.clone()), name)
+    }
+
+    // getter 绑定到 object 后立即调用，方法绑定到 object 后返回
+    fn property_value(
+        &mut self,
+        property: Option<Property>,
+        object: Value,
+        name: &Token,
+    ) -> Result<Value, RuntimeError> {
+        match property {
+            Some(Property::Getter(getter)) => {
+                getter
+                    .bind(object)
+                    .call_with_named(self, name, Vec::new(), Vec::new())
+            }
+            Some(Property::Method(method)) => Ok(Callable(Rc::new(method.bind(object)))),
+            None => Err(RuntimeError::new(
+                name.clone(),
+                format!("Undefined property '{}'.", name.lexeme),
+            )),
+        }
+    }
+
+    // 写入实例的属性：定义了 setter 时交给 setter，否则直接写字段。
+    // 只有 getter 的属性是只读的
+    fn set_property(
+        &mut self,
+        instance: &Rc<RefCell<LoxInstance>>,
+        name: &Token,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        let class = instance.borrow().class().clone();
+        if let Some(setter) = class.find_setter(&name.lexeme) {
+            setter.bind(Instance(instance.clone())).call_with_named(
+                self,
+                name,
+                vec![value],
+                Vec::new(),
+            )?;
+            return Ok(());
+        }
+        if let Some(Property::Getter(_)) = class.find_property(&name.lexeme) {
+            return Err(RuntimeError::new(
+                name.clone(),
+                format!("Property '{}' has no setter.", name.lexeme),
+            ));
+        }
+        instance.borrow_mut().set(name, value);
+        Ok(())
+    }
+
+    // 调用对象上由解释器约定的方法，用于迭代器协议和运算符重载
+    fn call_method(
+        &mut self,
+        instance: &Rc<RefCell<LoxInstance>>,
+        name: &str,
+        keyword: &Token,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let name = Token::new(TokenType::IDENTIFIER, name.to_string(), None, keyword.line);
+        match self.get_property(instance, &name)? {
+            Callable(method) => method.call_with_named(self, keyword, arguments, Vec::new()),
+            _ => Err(RuntimeError::new(
+                keyword.clone(),
+                format!("'{}' must be a method.", name.lexeme),
+            )),
+        }
+    }
+
+    // 左操作数是定义了对应特殊方法的实例时，由该方法完成运算；返回 None 表示没有重载。
+    // 只定义了 __lt__ 时，其余比较运算由 __lt__ 和 ==（__eq__ 或同一性）推出
+    fn overloaded_operation(
+        &mut self,
+        operator: &Token,
+        operator_type: TokenType,
+        instance: &Rc<RefCell<LoxInstance>>,
+        right: &Value,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let method = match operator_type {
+            TokenType::PLUS => "__add__",
+            TokenType::MINUS => "__sub__",
+            TokenType::STAR => "__mul__",
+            TokenType::SLASH => "__div__",
+            TokenType::TILDE_SLASH => "__floordiv__",
+            TokenType::PERCENT => "__mod__",
+            TokenType::EQUAL_EQUAL | TokenType::BANG_EQUAL => "__eq__",
+            TokenType::LESS => "__lt__",
+            TokenType::LESS_EQUAL => "__le__",
+            TokenType::GREATER => "__gt__",
+            TokenType::GREATER_EQUAL => "__ge__",
+            _ => return Ok(None),
+        };
+        let arguments = vec![right.clone()];
+        if instance.borrow().has_property(method) {
+            let result = self.call_method(instance, method, operator, arguments)?;
+            return Ok(Some(match operator_type {
+                TokenType::EQUAL_EQUAL
+                | TokenType::LESS
+                | TokenType::LESS_EQUAL
+                | TokenType::GREATER
+                | TokenType::GREATER_EQUAL => Boolean(*result.as_ref()),
+                TokenType::BANG_EQUAL => Boolean(!result.as_ref()),
+                _ => result,
+            }));
+        }
+
+        let derived = matches!(
+            operator_type,
+            TokenType::LESS_EQUAL | TokenType::GREATER | TokenType::GREATER_EQUAL
+        );
+        if !derived || !instance.borrow().has_property("__lt__") {
+            return Ok(None);
+        }
+        let less = *self
+            .call_method(instance, "__lt__", operator, arguments)?
+            .as_ref();
+        let result = match operator_type {
+            TokenType::GREATER_EQUAL => !less,
+            _ => {
+                let left = Instance(instance.clone());
+                let equal =
+                    self.binary_operation(operator, TokenType::EQUAL_EQUAL, left, right.clone())?;
+                if operator_type == TokenType::LESS_EQUAL {
+                    less || *equal.as_ref()
+                } else {
+                    !less && !equal.as_ref()
+                }
+            }
+        };
+        Ok(Some(Boolean(result)))
+    }
+
+    // 把值转换成 print、插值和 str() 输出的字符串；实例可以定义 __str__ 自定义结果，
+    // 列表和 map 中的元素同样如此
+    pub(crate) fn stringify(
+        &mut self,
+        token: &Token,
+        value: &Value,
+    ) -> Result<std::string::String, RuntimeError> {
+        self.stringify_nested(token, value, &mut Vec::new())
+    }
+
+    // enclosing 记录正在转换的列表和 map，容器包含自身时输出 [...] 或 {...}。
+    // enum 变体的字段也逐个递归转换，这样字段里的实例会用到 __str__
+    fn stringify_nested(
+        &mut self,
+        token: &Token,
+        value: &Value,
+        enclosing: &mut Vec<*const ()>,
+    ) -> Result<std::string::String, RuntimeError> {
+        match value {
+            Instance(instance) if instance.borrow().has_property("__str__") => {
+                match self.call_method(instance, "__str__", token, Vec::new())? {
+                    String(s) => Ok(s),
+                    _ => Err(RuntimeError::new(
+                        token.clone(),
+                        "__str__ must return a string.".to_string(),
+                    )),
+                }
+            }
+            List(elements) => {
+                let address = Rc::as_ptr(elements) as *const ();
+                if enclosing.contains(&address) {
+                    return Ok("[...]".to_string());
+                }
+                let elements = elements.borrow().clone();
+                enclosing.push(address);
+                let mut parts = Vec::with_capacity(elements.len());
+                for element in &elements {
+                    parts.push(self.stringify_nested(token, element, enclosing)?);
+                }
+                enclosing.pop();
+                Ok(format!("[{}]", parts.join(", ")))
+            }
+            Map(map) => {
+                let address = Rc::as_ptr(map) as *const ();
+                if enclosing.contains(&address) {
+                    return Ok("{...}".to_string());
+                }
+                let entries: Vec<(MapKey, Value)> = map.borrow().entries().cloned().collect();
+                enclosing.push(address);
+                let mut parts = Vec::with_capacity(entries.len());
+                for (key, value) in &entries {
+                    let value = self.stringify_nested(token, value, enclosing)?;
+                    parts.push(format!("{}: {}", key, value));
+                }
+                enclosing.pop();
+                Ok(format!("{{{}}}", parts.join(", ")))
+            }
+            Variant(variant) if !variant.payload.is_empty() => {
+                let mut parts = Vec::with_capacity(variant.payload.len());
+                for value in &variant.payload {
+                    parts.push(self.stringify_nested(token, value, enclosing)?);
+                }
+                Ok(format!(
+                    "{}.{}({})",
+                    variant.lox_enum.name,
+                    variant.name,
+                    parts.join(", ")
+                ))
+            }
+            _ => Ok(value.to_string()),
+        }
+    }
+
+    // 匹配成功时把模式绑定的变量依次放入 bindings
+    fn match_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        bindings: &mut Vec<(Token, Value)>,
+    ) -> Result<bool, RuntimeError> {
+        match pattern {
+            Pattern::Literal { value: literal, .. } => Ok(Self::literal_value(literal) == *value),
+            Pattern::Wildcard { .. } => Ok(true),
+            Pattern::Binding { name } => {
+                bindings.push((name.clone(), value.clone()));
+                Ok(true)
+            }
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+                ..
+            } => {
+                let (start, end) = (Self::literal_value(start), Self::literal_value(end));
+                // 类型不可比较时 partial_cmp 为 None，比较结果都是 false
+                Ok(*value >= start
+                    && if *inclusive {
+                        *value <= end
+                    } else {
+                        *value < end
+                    })
+            }
+            Pattern::Variant {
+                id,
+                enum_name,
+                variant,
+                fields,
+            } => {
+                let Enum(lox_enum) = self.look_up_variable(*id, enum_name)? else {
+                    return Err(RuntimeError::new(
+                        enum_name.clone(),
+                        format!("'{}' is not an enum.", enum_name.lexeme),
+                    ));
+                };
+                let Some(arity) = lox_enum.arity_of(&variant.lexeme) else {
+                    return Err(RuntimeError::new(
+                        variant.clone(),
+                        format!(
+                            "Enum '{}' has no variant '{}'.",
+                            lox_enum.name, variant.lexeme
+                        ),
+                    ));
+                };
+                if let Some(fields) = fields {
+                    if fields.len() != arity {
+                        return Err(RuntimeError::new(
+                            variant.clone(),
+                            format!(
+                                "Variant '{}' has {} fields but the pattern has {}.",
+                                variant.lexeme,
+                                arity,
+                                fields.len()
+                            ),
+                        ));
+                    }
+                }
+                let Variant(actual) = value else {
+                    return Ok(false);
+                };
+                if !Rc::ptr_eq(&actual.lox_enum, &lox_enum) || actual.name != variant.lexeme {
+                    return Ok(false);
+                }
+                for (field, element) in fields.iter().flatten().zip(&actual.payload) {
+                    if !self.match_pattern(field, element, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    fn literal_value(literal: &Literal) -> Value {
+        match literal {
+            Literal::String(s) => String(s.clone()),
+            Literal::Int(i) => Int(*i),
+            Literal::Number(n) => Number(*n),
+            Literal::Bool(b) => Boolean(*b),
+            Literal::Nil => Nil,
+        }
+    }
+
+    // 实例通过 __index__ 和 __setindex__ 支持下标访问
+    fn index_get(
+        &mut self,
+        bracket: &Token,
+        object: &Value,
+        index: &Value,
+    ) -> Result<Value, RuntimeError> {
+        match object {
+            Instance(instance) if instance.borrow().has_property("__index__") => {
+                self.call_method(instance, "__index__", bracket, vec![index.clone()])
+            }
+            List(elements) => {
+                let elements = elements.borrow();
+                let position = Self::list_position(bracket, &elements, index)?;
+                Ok(elements[position].clone())
+            }
+            Map(map) => map
+                .borrow()
+                .get(&Self::map_key(bracket, index)?)
+                .ok_or_else(|| {
+                    RuntimeError::new(bracket.clone(), format!("Undefined key '{}'.", index))
+                }),
+            _ => Err(RuntimeError::new(
+                bracket.clone(),
+                "Only lists and maps can be indexed.".to_string(),
+            )),
+        }
+    }
+
+    fn index_set(
+        &mut self,
+        bracket: &Token,
+        object: &Value,
+        index: &Value,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        match object {
+            Instance(instance) if instance.borrow().has_property("__setindex__") => {
+                let arguments = vec![index.clone(), value];
+                self.call_method(instance, "__setindex__", bracket, arguments)?;
+                Ok(())
+            }
+            List(elements) => {
+                let position = Self::list_position(bracket, &elements.borrow(), index)?;
+                elements.borrow_mut()[position] = value;
+                Ok(())
+            }
+            Map(map) => {
+                map.borrow_mut().set(Self::map_key(bracket, index)?, value);
+                Ok(())
+            }
+            _ => Err(RuntimeError::new(
+                bracket.clone(),
+                "Only lists and maps can be indexed.".to_string(),
+            )),
+        }
+    }
+
+    fn map_key(token: &Token, key: &Value) -> Result<MapKey, RuntimeError> {
+        MapKey::from_value(key).ok_or_else(|| {
+            RuntimeError::new(
+                token.clone(),
+                "Map keys must be strings, numbers, booleans or nil.".to_string(),
+            )
+        })
+    }
+
+    // 把下标换算成 Vec 中的位置，负数下标从末尾开始计数
+    fn list_position(
+        bracket: &Token,
+        elements: &[Value],
+        index: &Value,
+    ) -> Result<usize, RuntimeError> {
+        if !index.is_number() {
+            return Err(RuntimeError::new(
+                bracket.clone(),
+                "List index must be a number.".to_string(),
+            ));
+        }
+        let Some(n) = index.as_integer() else {
+            return Err(RuntimeError::new(
+                bracket.clone(),
+                "List index must be an integer.".to_string(),
+            ));
+        };
+        let len = elements.len() as i64;
+        let position = if n < 0 { len + n } else { n };
+        if position < 0 || position >= len {
+            return Err(RuntimeError::new(
+                bracket.clone(),
+                format!(
+                    "List index {} out of range for list of length {}.",
+                    index,
+                    elements.len()
+                ),
+            ));
+        }
+        Ok(position as usize)
+    }
+
+    fn arithmetic(
+        operator: &Token,
+        operation: Arithmetic,
+        left: &Value,
+        right: &Value,
+    ) -> Result<Value, RuntimeError> {
+        Value::arithmetic(operation, left, right)
+            .map_err(|message| RuntimeError::new(operator.clone(), message))
+    }
+
+    fn check_number_operand(operator: &Token, operand: &Value) -> Result<(), RuntimeError> {
+        if operand.is_number() {
+            return Ok(());
+        }
+        Err(RuntimeError::new(
+            operator.clone(),
+            "Operand must be a number.".to_string(),
+        ))
+    }
+
+    fn check_number_operands(
+        operator: &Token,
+        left: &Value,
+        right: &Value,
+    ) -> Result<(), RuntimeError> {
+        if left.is_number() && right.is_number() {
+            return Ok(());
         }
+        Err(RuntimeError::new(
+            operator.clone(),
+            "Operands must be numbers.".to_string(),
+        ))
+    }
+}
+
+impl crate::expr::Visitor<Result<Value, RuntimeError>> for Interpreter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Value, RuntimeError> {
+        let left_value = self.evaluate(left)?;
+        let right_value = self.evaluate(right)?;
+
+        self.binary_operation(operator, operator.token_type, left_value, right_value)
     }
 
     fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<Value, RuntimeError> {
@@ -141,33 +986,40 @@ impl crate::expr::Visitor<Result<Value, RuntimeError>> for Interpreter {
     }
 
     fn visit_literal_expr(&mut self, value: &Literal) -> Result<Value, RuntimeError> {
-        match value {
-            Literal::String(s) => Ok(String(s.clone())),
-            Literal::Number(n) => Ok(Number(*n)),
-            Literal::Bool(b) => Ok(Boolean(*b)),
-            Literal::Nil => Ok(Nil),
-        }
+        Ok(Self::literal_value(value))
     }
 
     fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<Value, RuntimeError> {
         let right_value = self.evaluate(right)?;
         match operator.token_type {
             TokenType::MINUS => {
+                if let Instance(instance) = &right_value {
+                    if instance.borrow().has_property("__neg__") {
+                        return self.call_method(instance, "__neg__", operator, Vec::new());
+                    }
+                }
                 Self::check_number_operand(operator, &right_value)?;
-                Ok(-right_value)
+                right_value
+                    .negate()
+                    .map_err(|message| RuntimeError::new(operator.clone(), message))
             }
             TokenType::BANG => Ok(Boolean(!right_value.as_ref())),
             _ => unreachable!(),
         }
     }
 
-    fn visit_variable_expr(&mut self, name: &Token) -> Result<Value, RuntimeError> {
-        self.environment.borrow().get(name)
+    fn visit_variable_expr(&mut self, id: usize, name: &Token) -> Result<Value, RuntimeError> {
+        self.look_up_variable(id, name)
     }
 
-    fn visit_assign_expr(&mut self, name: &Token, value: &Expr) -> Result<Value, RuntimeError> {
+    fn visit_assign_expr(
+        &mut self,
+        id: usize,
+        name: &Token,
+        value: &Expr,
+    ) -> Result<Value, RuntimeError> {
         let value = self.evaluate(value)?;
-        self.environment.borrow_mut().assign(name, value.clone())?;
+        self.assign_variable(id, name, value.clone())?;
         Ok(value)
     }
 
@@ -188,36 +1040,303 @@ impl crate::expr::Visitor<Result<Value, RuntimeError>> for Interpreter {
 
         self.evaluate(right)
     }
+
+    fn visit_call_expr(
+        &mut self,
+        callee: &Expr,
+        paren: &Token,
+        arguments: &[Argument],
+    ) -> Result<Value, RuntimeError> {
+        let callee = self.evaluate(callee)?;
+
+        let mut positional = Vec::with_capacity(arguments.len());
+        let mut named = Vec::new();
+        for argument in arguments {
+            let value = self.evaluate(argument.value())?;
+            match argument {
+                Argument::Positional { .. } => positional.push(value),
+                Argument::Named { name, .. } => named.push((name.clone(), value)),
+                Argument::Spread { ellipsis, .. } => {
+                    let List(elements) = value else {
+                        return Err(RuntimeError::new(
+                            ellipsis.clone(),
+                            "Only lists can be spread into arguments.".to_string(),
+                        ));
+                    };
+                    positional.extend(elements.borrow().iter().cloned());
+                }
+            }
+        }
+
+        match callee {
+            Callable(function) => function.call_with_named(self, paren, positional, named),
+            Class(class) => LoxClass::call(&class, self, paren, positional, named),
+            _ => Err(RuntimeError::new(
+                paren.clone(),
+                "Can only call functions and classes.".to_string(),
+            )),
+        }
+    }
+
+    fn visit_get_expr(&mut self, object: &Expr, name: &Token) -> Result<Value, RuntimeError> {
+        match self.evaluate(object)? {
+            Instance(instance) => self.get_property(&instance, name),
+            Class(class) => LoxClass::get(&class, name),
+            Module(module) => module.get(name),
+            Enum(lox_enum) => LoxEnum::get(&lox_enum, name),
+            _ => Err(RuntimeError::new(
+                name.clone(),
+                "Only instances have properties.".to_string(),
+            )),
+        }
+    }
+
+    fn visit_set_expr(
+        &mut self,
+        object: &Expr,
+        name: &Token,
+        value: &Expr,
+    ) -> Result<Value, RuntimeError> {
+        let Instance(instance) = self.evaluate(object)? else {
+            return Err(RuntimeError::new(
+                name.clone(),
+                "Only instances have fields.".to_string(),
+            ));
+        };
+        let value = self.evaluate(value)?;
+        self.set_property(&instance, name, value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_this_expr(&mut self, id: usize, keyword: &Token) -> Result<Value, RuntimeError> {
+        self.look_up_variable(id, keyword)
+    }
+
+    fn visit_super_expr(
+        &mut self,
+        id: usize,
+        keyword: &Token,
+        method: &Token,
+    ) -> Result<Value, RuntimeError> {
+        let distance = *self
+            .locals
+            .get(&id)
+            .expect("Resolver always binds 'super' to a local scope.");
+        let Class(superclass) = self.environment.borrow().get_at(distance, keyword)? else {
+            unreachable!("'super' is always bound to a class.")
+        };
+        // this 所在的环境总是紧挨在 super 的环境里面
+        let this = Token::new(TokenType::THIS, "this".to_string(), None, keyword.line);
+        let object = self.environment.borrow().get_at(distance - 1, &this)?;
+
+        // 类方法中的 this 是类本身，super 此时查找父类的类方法
+        let property = match &object {
+            Class(_) => superclass
+                .find_class_method(&method.lexeme)
+                .map(Property::Method),
+            _ => superclass.find_property(&method.lexeme),
+        };
+        self.property_value(property, object, method)
+    }
+
+    fn visit_function_expr(
+        &mut self,
+        declaration: &Rc<FunctionDecl>,
+    ) -> Result<Value, RuntimeError> {
+        let function = LoxFunction::new(declaration.clone(), self.environment.clone(), false);
+        Ok(Callable(Rc::new(function)))
+    }
+
+    fn visit_list_expr(&mut self, elements: &[Expr]) -> Result<Value, RuntimeError> {
+        let mut values = Vec::with_capacity(elements.len());
+        for element in elements {
+            values.push(self.evaluate(element)?);
+        }
+        Ok(List(Rc::new(RefCell::new(values))))
+    }
+
+    fn visit_index_expr(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+    ) -> Result<Value, RuntimeError> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        self.index_get(bracket, &object, &index)
+    }
+
+    fn visit_index_set_expr(
+        &mut self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> Result<Value, RuntimeError> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        let value = self.evaluate(value)?;
+        self.index_set(bracket, &object, &index, value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_map_expr(
+        &mut self,
+        brace: &Token,
+        entries: &[(Expr, Expr)],
+    ) -> Result<Value, RuntimeError> {
+        let mut map = LoxMap::new();
+        for (key, value) in entries {
+            let key = self.evaluate(key)?;
+            let value = self.evaluate(value)?;
+            map.set(Self::map_key(brace, &key)?, value);
+        }
+        Ok(Map(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_interpolation_expr(
+        &mut self,
+        token: &Token,
+        parts: &[Expr],
+    ) -> Result<Value, RuntimeError> {
+        let mut result = std::string::String::new();
+        for part in parts {
+            let value = self.evaluate(part)?;
+            result.push_str(&self.stringify(token, &value)?);
+        }
+        Ok(String(result))
+    }
+
+    fn visit_compound_assign_expr(
+        &mut self,
+        target: &Expr,
+        operator: &Token,
+        value: &Expr,
+    ) -> Result<Value, RuntimeError> {
+        let operator_type = match operator.token_type {
+            TokenType::PLUS_EQUAL => TokenType::PLUS,
+            TokenType::MINUS_EQUAL => TokenType::MINUS,
+            TokenType::STAR_EQUAL => TokenType::STAR,
+            TokenType::SLASH_EQUAL => TokenType::SLASH,
+            _ => unreachable!("Invalid compound assignment operator"),
+        };
+        let (_, new) = self.update_target(target, |interpreter, old| {
+            let value = interpreter.evaluate(value)?;
+            interpreter.binary_operation(operator, operator_type, old, value)
+        })?;
+        Ok(new)
+    }
+
+    fn visit_increment_expr(
+        &mut self,
+        target: &Expr,
+        operator: &Token,
+        postfix: bool,
+    ) -> Result<Value, RuntimeError> {
+        let operator_type = match operator.token_type {
+            TokenType::PLUS_PLUS => TokenType::PLUS,
+            TokenType::MINUS_MINUS => TokenType::MINUS,
+            _ => unreachable!("Invalid increment operator"),
+        };
+        let (old, new) = self.update_target(target, |interpreter, old| {
+            // 实例交给 __add__ / __sub__ 处理，与 += 1、-= 1 一致；其余类型必须是数字
+            if !matches!(old, Instance(_)) {
+                Self::check_number_operand(operator, &old)?;
+            }
+            interpreter.binary_operation(operator, operator_type, old, Int(1))
+        })?;
+        Ok(if postfix { old } else { new })
+    }
+
+    fn visit_conditional_expr(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Expr,
+        else_branch: &Expr,
+    ) -> Result<Value, RuntimeError> {
+        if *self.evaluate(condition)?.as_ref() {
+            self.evaluate(then_branch)
+        } else {
+            self.evaluate(else_branch)
+        }
+    }
+
+    fn visit_destructure_assign_expr(
+        &mut self,
+        pattern: &AssignPattern,
+        _equals: &Token,
+        value: &Expr,
+    ) -> Result<Value, RuntimeError> {
+        // 右侧先完整求值，[a, b] = [b, a] 因此可以交换
+        let value = self.evaluate(value)?;
+        let values = match pattern {
+            AssignPattern::List {
+                bracket,
+                targets,
+                rest,
+            } => Self::unpack_list(bracket, &value, targets.len(), rest.is_some())?,
+            AssignPattern::Object { brace, names } => {
+                let names: Vec<&Token> = names
+                    .iter()
+                    .map(|name| match name {
+                        Expr::Variable { name, .. } => name,
+                        _ => unreachable!("Object pattern names are always variables."),
+                    })
+                    .collect();
+                self.unpack_object(brace, &names, &value)?
+            }
+        };
+        for (target, element) in pattern.targets().into_iter().zip(values) {
+            self.assign_target(target, element)?;
+        }
+        Ok(value)
+    }
 }
 
-impl crate::stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
-    fn visit_expression_stmt(&mut self, expr: &Expr) -> Result<(), RuntimeError> {
+impl crate::stmt::Visitor<Result<(), Unwind>> for Interpreter {
+    fn visit_expression_stmt(&mut self, expr: &Expr) -> Result<(), Unwind> {
         self.evaluate(expr)?;
         Ok(())
     }
 
-    fn visit_print_stmt(&mut self, expr: &Expr) -> Result<(), RuntimeError> {
+    fn visit_print_stmt(&mut self, keyword: &Token, expr: &Expr) -> Result<(), Unwind> {
         let value = self.evaluate(expr)?;
-        println!("{}", value);
+        println!("{}", self.stringify(keyword, &value)?);
         Ok(())
     }
 
-    fn visit_var_stmt(
-        &mut self,
-        name: &Token,
-        initializer: Option<&Expr>,
-    ) -> Result<(), RuntimeError> {
+    fn visit_var_stmt(&mut self, name: &Token, initializer: Option<&Expr>) -> Result<(), Unwind> {
         let mut value = Nil;
         if let Some(v) = initializer {
             value = self.evaluate(v)?;
         }
+        self.environment.borrow_mut().define(name, value)?;
+        Ok(())
+    }
+
+    fn visit_const_stmt(&mut self, name: &Token, initializer: &Expr) -> Result<(), Unwind> {
+        let value = self.evaluate(initializer)?;
         self.environment
             .borrow_mut()
-            .define(name.lexeme.clone(), value);
+            .define_constant(name, value, name.line)?;
+        Ok(())
+    }
+
+    fn visit_destructure_stmt(
+        &mut self,
+        pattern: &Destructure,
+        initializer: &Expr,
+    ) -> Result<(), Unwind> {
+        let value = self.evaluate(initializer)?;
+        let values = self.destructure(pattern, &value)?;
+        let mut environment = self.environment.borrow_mut();
+        for (name, value) in pattern.names().into_iter().zip(values) {
+            environment.define(name, value)?;
+        }
         Ok(())
     }
 
-    fn visit_block_stmt(&mut self, statements: &Vec<Stmt>) -> Result<(), RuntimeError> {
+    fn visit_block_stmt(&mut self, statements: &[Stmt]) -> Result<(), Unwind> {
         self.execute_block(
             statements,
             Environment::new_enclosing(self.environment.clone()),
@@ -229,7 +1348,7 @@ impl crate::stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
         condition: &Expr,
         then_branch: &Stmt,
         else_branch: Option<&Stmt>,
-    ) -> Result<(), RuntimeError> {
+    ) -> Result<(), Unwind> {
         if *self.evaluate(condition)?.as_ref() {
             self.execute(then_branch)?;
         } else if let Some(else_branch) = else_branch {
@@ -239,11 +1358,327 @@ impl crate::stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
         Ok(())
     }
 
-    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> Result<(), RuntimeError> {
+    fn visit_while_stmt(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: Option<&Expr>,
+        label: Option<&Token>,
+    ) -> Result<(), Unwind> {
         while *self.evaluate(condition)?.as_ref() {
-            self.execute(body)?;
+            match self.execute(body) {
+                Ok(()) => {}
+                Err(Unwind::Break(target)) if Self::targets_loop(target.as_deref(), label) => break,
+                Err(Unwind::Continue(target)) if Self::targets_loop(target.as_deref(), label) => {}
+                Err(e) => return Err(e),
+            }
+            if let Some(increment) = increment {
+                self.evaluate(increment)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &Token,
+        keyword: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+        label: Option<&Token>,
+    ) -> Result<(), Unwind> {
+        // 内置集合先取快照，循环体里修改集合不会影响本次遍历
+        let values: Vec<Value> = match self.evaluate(iterable)? {
+            List(elements) => elements.borrow().clone(),
+            String(s) => s.chars().map(|c| String(c.to_string())).collect(),
+            Map(map) => map
+                .borrow()
+                .entries()
+                .map(|(key, _)| key.to_value())
+                .collect(),
+            // 迭代器协议：iter() 返回迭代器，迭代器提供 has_next() 和 next()；
+            // 本身就有 next() 的对象直接作为迭代器
+            Instance(instance) => {
+                let iterator = if instance.borrow().has_property("iter") {
+                    match self.call_method(&instance, "iter", keyword, Vec::new())? {
+                        Instance(iterator) => iterator,
+                        _ => {
+                            return Err(RuntimeError::new(
+                                keyword.clone(),
+                                "iter() must return an object.".to_string(),
+                            )
+                            .into())
+                        }
+                    }
+                } else if instance.borrow().has_property("next") {
+                    instance
+                } else {
+                    return Err(RuntimeError::new(
+                        keyword.clone(),
+                        format!(
+                            "{} instance is not iterable.",
+                            instance.borrow().class_name()
+                        ),
+                    )
+                    .into());
+                };
+                while *self
+                    .call_method(&iterator, "has_next", keyword, Vec::new())?
+                    .as_ref()
+                {
+                    let value = self.call_method(&iterator, "next", keyword, Vec::new())?;
+                    if !self.iterate(name, value, body, label)? {
+                        break;
+                    }
+                }
+                return Ok(());
+            }
+            _ => {
+                return Err(RuntimeError::new(
+                    keyword.clone(),
+                    "Can only iterate over lists, strings, maps and iterable objects.".to_string(),
+                )
+                .into())
+            }
+        };
+        for value in values {
+            if !self.iterate(name, value, body, label)? {
+                break;
+            }
         }
+        Ok(())
+    }
+
+    fn visit_enum_stmt(
+        &mut self,
+        name: &Token,
+        variants: &[(Token, Vec<Token>)],
+    ) -> Result<(), Unwind> {
+        let variants = variants
+            .iter()
+            .map(|(variant, fields)| (variant.lexeme.clone(), fields.len()))
+            .collect();
+        let lox_enum = LoxEnum::new(name.lexeme.clone(), variants);
+        self.environment
+            .borrow_mut()
+            .define(name, Enum(Rc::new(lox_enum)))?;
+        Ok(())
+    }
 
+    fn visit_match_stmt(
+        &mut self,
+        keyword: &Token,
+        subject: &Expr,
+        arms: &[MatchArm],
+    ) -> Result<(), Unwind> {
+        let value = self.evaluate(subject)?;
+        for arm in arms {
+            let mut bindings = Vec::new();
+            if !self.match_pattern(&arm.pattern, &value, &mut bindings)? {
+                continue;
+            }
+            // 守卫和分支体在同一个新环境中求值，模式绑定的变量定义在这里
+            let environment = Environment::new_enclosing(self.environment.clone());
+            for (name, value) in bindings {
+                environment.borrow_mut().define(&name, value)?;
+            }
+            if let Some(guard) = &arm.guard {
+                let previous = std::mem::replace(&mut self.environment, environment.clone());
+                let passed = self.evaluate(guard);
+                self.environment = previous;
+                if !*passed?.as_ref() {
+                    continue;
+                }
+            }
+            return self.execute_block(std::slice::from_ref(&arm.body), environment);
+        }
+        Err(RuntimeError::new(
+            keyword.clone(),
+            format!("No match arm matches value {}.", value),
+        )
+        .into())
+    }
+
+    fn visit_break_stmt(&mut self, _keyword: &Token, label: Option<&Token>) -> Result<(), Unwind> {
+        Err(Unwind::Break(label.map(|label| label.lexeme.clone())))
+    }
+
+    fn visit_continue_stmt(
+        &mut self,
+        _keyword: &Token,
+        label: Option<&Token>,
+    ) -> Result<(), Unwind> {
+        Err(Unwind::Continue(label.map(|label| label.lexeme.clone())))
+    }
+
+    fn visit_import_stmt(
+        &mut self,
+        _keyword: &Token,
+        path: &Token,
+        alias: Option<&Token>,
+    ) -> Result<(), Unwind> {
+        let module = self.load_module(path)?;
+        match alias {
+            Some(alias) => self
+                .environment
+                .borrow_mut()
+                .define(alias, Module(module))?,
+            None => {
+                for name in &module.exports {
+                    let token = Token::new(TokenType::IDENTIFIER, name.clone(), None, path.line);
+                    let value = module.get(&token)?;
+                    // 导入的常量在这里同样不能重新赋值
+                    let mut environment = self.environment.borrow_mut();
+                    match module.constant_line(name) {
+                        Some(line) => environment.define_constant(&token, value, line)?,
+                        None => environment.define(&token, value)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_throw_stmt(&mut self, keyword: &Token, value: &Expr) -> Result<(), Unwind> {
+        let value = self.evaluate(value)?;
+        Err(RuntimeError::thrown(keyword.clone(), value).into())
+    }
+
+    fn visit_try_stmt(
+        &mut self,
+        body: &[Stmt],
+        catch: Option<&CatchClause>,
+        finally: Option<&[Stmt]>,
+    ) -> Result<(), Unwind> {
+        let mut result =
+            self.execute_block(body, Environment::new_enclosing(self.environment.clone()));
+
+        if let Some(catch) = catch {
+            if let Err(Unwind::Error(error)) = result {
+                let environment = Environment::new_enclosing(self.environment.clone());
+                environment
+                    .borrow_mut()
+                    .define(&catch.name, self.error_value(error))?;
+                result = self.execute_block(&catch.body, environment);
+            }
+        }
+
+        // finally 总会执行；如果它自身中断了控制流，以它的结果为准
+        if let Some(finally) = finally {
+            self.execute_block(
+                finally,
+                Environment::new_enclosing(self.environment.clone()),
+            )?;
+        }
+        result
+    }
+
+    fn visit_function_stmt(&mut self, declaration: &Rc<FunctionDecl>) -> Result<(), Unwind> {
+        let function = LoxFunction::new(declaration.clone(), self.environment.clone(), false);
+        self.environment
+            .borrow_mut()
+            .define(&declaration.name, Callable(Rc::new(function)))?;
+        Ok(())
+    }
+
+    fn visit_return_stmt(&mut self, _keyword: &Token, value: Option<&Expr>) -> Result<(), Unwind> {
+        let value = match value {
+            Some(value) => self.evaluate(value)?,
+            None => Nil,
+        };
+        Err(Unwind::Return(value))
+    }
+
+    fn visit_class_stmt(
+        &mut self,
+        name: &Token,
+        superclass: Option<&Expr>,
+        traits: &[Expr],
+        body: &ClassBody,
+    ) -> Result<(), Unwind> {
+        let superclass = match superclass {
+            Some(superclass_expr) => match self.evaluate(superclass_expr)? {
+                Class(class) => Some(class),
+                _ => {
+                    let Expr::Variable { name, .. } = superclass_expr else {
+                        unreachable!("Superclass is always a variable expression.")
+                    };
+                    return Err(RuntimeError::new(
+                        name.clone(),
+                        "Superclass must be a class.".to_string(),
+                    )
+                    .into());
+                }
+            },
+            None => None,
+        };
+        let traits = traits
+            .iter()
+            .map(|trait_expr| match self.evaluate(trait_expr)? {
+                Trait(lox_trait) => Ok(lox_trait),
+                _ => {
+                    let Expr::Variable { name, .. } = trait_expr else {
+                        unreachable!("Trait is always a variable expression.")
+                    };
+                    Err(RuntimeError::new(
+                        name.clone(),
+                        "Can only mix in traits.".to_string(),
+                    ))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.environment.borrow_mut().define(name, Nil)?;
+
+        let enclosing = self.environment.clone();
+        if let Some(superclass) = &superclass {
+            self.environment = Environment::new_enclosing(enclosing.clone());
+            self.environment
+                .borrow_mut()
+                .bind("super".to_string(), Class(superclass.clone()));
+        }
+
+        let mut members = self.class_members(body);
+        self.environment = enclosing;
+        if let Err(message) = members.mix_in(&traits) {
+            return Err(RuntimeError::new(name.clone(), message).into());
+        }
+
+        let class = LoxClass::new(name.lexeme.clone(), superclass, members);
+        for lox_trait in &traits {
+            if let Some(method) = lox_trait
+                .required
+                .iter()
+                .find(|method| !class.implements(&method.lexeme))
+            {
+                return Err(RuntimeError::new(
+                    name.clone(),
+                    format!(
+                        "Class '{}' must implement '{}' required by trait '{}'.",
+                        name.lexeme, method.lexeme, lox_trait.name
+                    ),
+                )
+                .into());
+            }
+        }
+        self.environment
+            .borrow_mut()
+            .assign(name, Class(Rc::new(class)))?;
+        Ok(())
+    }
+
+    fn visit_trait_stmt(
+        &mut self,
+        name: &Token,
+        body: &ClassBody,
+        required: &[Token],
+    ) -> Result<(), Unwind> {
+        let members = self.class_members(body);
+        let lox_trait = LoxTrait::new(name.lexeme.clone(), members, required.to_vec());
+        self.environment
+            .borrow_mut()
+            .define(name, Trait(Rc::new(lox_trait)))?;
         Ok(())
     }
 }
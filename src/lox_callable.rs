@@ -0,0 +1,44 @@
+use crate::interpreter::Interpreter;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+use crate::value::Value;
+use std::fmt::{Debug, Display};
+
+pub(crate) trait LoxCallable: Debug + Display {
+    fn arity(&self) -> usize;
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        // 调用处的右括号，用于报告错误的位置
+        paren: &Token,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError>;
+
+    // 带命名参数的调用。只有用户定义的函数支持命名参数和默认值，
+    // 其余可调用对象要求位置参数的个数正好等于 arity
+    fn call_with_named(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Value>,
+        named: Vec<(Token, Value)>,
+    ) -> Result<Value, RuntimeError> {
+        if let Some((name, _)) = named.first() {
+            return Err(RuntimeError::new(
+                name.clone(),
+                format!("{} doesn't accept named arguments.", self),
+            ));
+        }
+        if arguments.len() != self.arity() {
+            return Err(RuntimeError::new(
+                paren.clone(),
+                format!(
+                    "Expected {} arguments but got {}.",
+                    self.arity(),
+                    arguments.len()
+                ),
+            ));
+        }
+        self.call(interpreter, paren, arguments)
+    }
+}
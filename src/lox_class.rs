@@ -0,0 +1,214 @@
+use crate::interpreter::Interpreter;
+use crate::lox_callable::LoxCallable;
+use crate::lox_function::LoxFunction;
+use crate::lox_instance::LoxInstance;
+use crate::lox_trait::LoxTrait;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+use crate::value::Value;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::rc::Rc;
+
+type Methods = HashMap<String, Rc<LoxFunction>>;
+
+// class_methods 在 ClassMembers::kinds 中的位置
+const CLASS_METHODS: usize = 3;
+
+// 类中各类成员的名字到函数的映射
+#[derive(Debug, Default)]
+pub(crate) struct ClassMembers {
+    pub(crate) methods: Methods,
+    pub(crate) getters: Methods,
+    pub(crate) setters: Methods,
+    pub(crate) class_methods: Methods,
+}
+
+impl ClassMembers {
+    fn kinds(&self) -> [&Methods; 4] {
+        [
+            &self.methods,
+            &self.getters,
+            &self.setters,
+            &self.class_methods,
+        ]
+    }
+
+    fn kinds_mut(&mut self) -> [&mut Methods; 4] {
+        [
+            &mut self.methods,
+            &mut self.getters,
+            &mut self.setters,
+            &mut self.class_methods,
+        ]
+    }
+
+    // 把 trait 的成员并入类自身的成员。类方法通过类访问，其余成员通过实例访问，
+    // 成员名按 (是否类方法, 名字) 区分。类在同一命名空间中自己定义的名字优先；
+    // 类没有定义、却有两个 trait 提供了同名成员（无论种类）时返回冲突信息
+    pub(crate) fn mix_in(&mut self, traits: &[Rc<LoxTrait>]) -> Result<(), String> {
+        for (i, lox_trait) in traits.iter().enumerate() {
+            if traits[..i].iter().any(|other| Rc::ptr_eq(other, lox_trait)) {
+                return Err(format!(
+                    "Trait '{}' is mixed in more than once.",
+                    lox_trait.name
+                ));
+            }
+        }
+
+        let own: HashSet<(bool, String)> = self
+            .kinds()
+            .into_iter()
+            .enumerate()
+            .flat_map(|(kind, members)| {
+                members
+                    .keys()
+                    .map(move |name| (kind == CLASS_METHODS, name.clone()))
+            })
+            .collect();
+        // 已并入的成员到提供它的 trait 在 traits 中的位置
+        let mut origins: HashMap<(bool, &str), usize> = HashMap::new();
+        for (i, lox_trait) in traits.iter().enumerate() {
+            for (kind, members) in lox_trait.members.kinds().into_iter().enumerate() {
+                for (name, method) in members {
+                    let is_class_method = kind == CLASS_METHODS;
+                    if own.contains(&(is_class_method, name.clone())) {
+                        continue;
+                    }
+                    let key = (is_class_method, name.as_str());
+                    match origins.get(&key) {
+                        Some(&other) if other != i => {
+                            return Err(format!(
+                                "'{}' is defined by both trait '{}' and trait '{}'.",
+                                name, traits[other].name, lox_trait.name
+                            ));
+                        }
+                        _ => {}
+                    }
+                    origins.insert(key, i);
+                    self.kinds_mut()[kind].insert(name.clone(), method.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// 实例上可以读取的类成员：getter 在读取时调用，方法绑定到实例后返回
+pub(crate) enum Property {
+    Getter(Rc<LoxFunction>),
+    Method(Rc<LoxFunction>),
+}
+
+#[derive(Debug)]
+pub(crate) struct LoxClass {
+    pub(crate) name: String,
+    superclass: Option<Rc<LoxClass>>,
+    members: ClassMembers,
+}
+
+impl LoxClass {
+    pub(crate) fn new(
+        name: String,
+        superclass: Option<Rc<LoxClass>>,
+        members: ClassMembers,
+    ) -> Self {
+        LoxClass {
+            name,
+            superclass,
+            members,
+        }
+    }
+
+    // 先找自身的成员，再沿继承链向上找
+    fn find(&self, kind: fn(&ClassMembers) -> &Methods, name: &str) -> Option<Rc<LoxFunction>> {
+        if let Some(method) = kind(&self.members).get(name) {
+            return Some(method.clone());
+        }
+        self.superclass
+            .as_ref()
+            .and_then(|superclass| superclass.find(kind, name))
+    }
+
+    pub(crate) fn find_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        self.find(|members| &members.methods, name)
+    }
+
+    // getter 和方法逐层查找：子类中同名的 getter 或方法覆盖父类中的任意一种
+    pub(crate) fn find_property(&self, name: &str) -> Option<Property> {
+        if let Some(getter) = self.members.getters.get(name) {
+            return Some(Property::Getter(getter.clone()));
+        }
+        if let Some(method) = self.members.methods.get(name) {
+            return Some(Property::Method(method.clone()));
+        }
+        self.superclass
+            .as_ref()
+            .and_then(|superclass| superclass.find_property(name))
+    }
+
+    // 必需的方法可以由类自身、父类或其他 trait 以方法或 getter 的形式提供
+    pub(crate) fn implements(&self, name: &str) -> bool {
+        self.find_property(name).is_some()
+    }
+
+    pub(crate) fn find_setter(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        self.find(|members| &members.setters, name)
+    }
+
+    pub(crate) fn find_class_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        self.find(|members| &members.class_methods, name)
+    }
+
+    // 类方法绑定到类本身
+    pub(crate) fn get(class: &Rc<LoxClass>, name: &Token) -> Result<Value, RuntimeError> {
+        match class.find_class_method(&name.lexeme) {
+            Some(method) => Ok(Value::Callable(Rc::new(
+                method.bind(Value::Class(class.clone())),
+            ))),
+            None => Err(RuntimeError::new(
+                name.clone(),
+                format!("Undefined property '{}'.", name.lexeme),
+            )),
+        }
+    }
+
+    // 调用类即创建实例，实参交给 init 处理；没有 init 时不接受任何参数
+    pub(crate) fn call(
+        class: &Rc<LoxClass>,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Value>,
+        named: Vec<(Token, Value)>,
+    ) -> Result<Value, RuntimeError> {
+        let instance = Value::Instance(Rc::new(RefCell::new(LoxInstance::new(class.clone()))));
+        match class.find_method("init") {
+            Some(initializer) => {
+                initializer.bind(instance.clone()).call_with_named(
+                    interpreter,
+                    paren,
+                    arguments,
+                    named,
+                )?;
+            }
+            None if !arguments.is_empty() || !named.is_empty() => {
+                return Err(RuntimeError::new(
+                    paren.clone(),
+                    format!(
+                        "Expected 0 arguments but got {}.",
+                        arguments.len() + named.len()
+                    ),
+                ));
+            }
+            None => {}
+        }
+        Ok(instance)
+    }
+}
+
+impl Display for LoxClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
@@ -0,0 +1,106 @@
+use crate::interpreter::Interpreter;
+use crate::lox_callable::LoxCallable;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+use crate::value::Value;
+use std::fmt::{Debug, Display};
+use std::rc::Rc;
+
+// enum 声明本身：记录每个变体的名字和字段个数
+#[derive(Debug)]
+pub(crate) struct LoxEnum {
+    pub(crate) name: String,
+    variants: Vec<(String, usize)>,
+}
+
+impl LoxEnum {
+    pub(crate) fn new(name: String, variants: Vec<(String, usize)>) -> Self {
+        LoxEnum { name, variants }
+    }
+
+    pub(crate) fn arity_of(&self, variant: &str) -> Option<usize> {
+        self.variants
+            .iter()
+            .find(|(name, _)| name == variant)
+            .map(|(_, arity)| *arity)
+    }
+
+    // 没有字段的变体直接就是值，有字段的变体是构造函数
+    pub(crate) fn get(lox_enum: &Rc<LoxEnum>, name: &Token) -> Result<Value, RuntimeError> {
+        match lox_enum.arity_of(&name.lexeme) {
+            Some(0) => Ok(Value::Variant(Rc::new(EnumVariant {
+                lox_enum: lox_enum.clone(),
+                name: name.lexeme.clone(),
+                payload: Vec::new(),
+            }))),
+            Some(arity) => Ok(Value::Callable(Rc::new(VariantConstructor {
+                lox_enum: lox_enum.clone(),
+                name: name.lexeme.clone(),
+                arity,
+            }))),
+            None => Err(RuntimeError::new(
+                name.clone(),
+                format!("Enum '{}' has no variant '{}'.", lox_enum.name, name.lexeme),
+            )),
+        }
+    }
+}
+
+impl Display for LoxEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<enum {}>", self.name)
+    }
+}
+
+// enum 的值：标签加上构造时传入的字段，按结构比较相等
+#[derive(Debug)]
+pub(crate) struct EnumVariant {
+    pub(crate) lox_enum: Rc<LoxEnum>,
+    pub(crate) name: String,
+    pub(crate) payload: Vec<Value>,
+}
+
+impl PartialEq for EnumVariant {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.lox_enum, &other.lox_enum)
+            && self.name == other.name
+            && self.payload == other.payload
+    }
+}
+
+struct VariantConstructor {
+    lox_enum: Rc<LoxEnum>,
+    name: String,
+    arity: usize,
+}
+
+impl LoxCallable for VariantConstructor {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        _paren: &Token,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        Ok(Value::Variant(Rc::new(EnumVariant {
+            lox_enum: self.lox_enum.clone(),
+            name: self.name.clone(),
+            payload: arguments,
+        })))
+    }
+}
+
+impl Display for VariantConstructor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<fn {}.{}>", self.lox_enum.name, self.name)
+    }
+}
+
+impl Debug for VariantConstructor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
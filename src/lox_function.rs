@@ -0,0 +1,190 @@
+use crate::environment::Environment;
+use crate::interpreter::Interpreter;
+use crate::lox_callable::LoxCallable;
+use crate::runtime_error::RuntimeError;
+use crate::stmt::FunctionDecl;
+use crate::token::Token;
+use crate::token_type::TokenType;
+use crate::unwind::Unwind;
+use crate::value::Value;
+use std::cell::RefCell;
+use std::fmt::{Debug, Display};
+use std::rc::Rc;
+
+pub(crate) struct LoxFunction {
+    declaration: Rc<FunctionDecl>,
+    // 声明函数时所在的环境，调用结束后仍由函数持有，闭包因此能看到后续的赋值
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub(crate) fn new(
+        declaration: Rc<FunctionDecl>,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> Self {
+        LoxFunction {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    // 在闭包外再包一层只含 this 的环境，得到绑定到实例上的方法
+    pub(crate) fn bind(&self, instance: Value) -> LoxFunction {
+        let environment = Environment::new_enclosing(self.closure.clone());
+        environment.borrow_mut().bind("this".to_string(), instance);
+        LoxFunction::new(self.declaration.clone(), environment, self.is_initializer)
+    }
+
+    // 按参数表绑定实参：位置参数依次对应，命名参数按名字对应，rest 参数收集剩余的位置参数；
+    // 两者都没有提供的参数使用默认值，默认值在已绑定前面参数的环境中求值
+    fn bind_arguments(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Value>,
+        named: Vec<(Token, Value)>,
+    ) -> Result<Rc<RefCell<Environment>>, RuntimeError> {
+        let params = &self.declaration.params;
+        let has_rest = params.last().is_some_and(|param| param.rest);
+        let positional = params.len() - usize::from(has_rest);
+        if !has_rest && arguments.len() > positional {
+            let at_most = if params.iter().any(|param| param.default.is_some()) {
+                "at most "
+            } else {
+                ""
+            };
+            return Err(RuntimeError::new(
+                paren.clone(),
+                format!(
+                    "Expected {}{} arguments but got {}.",
+                    at_most,
+                    positional,
+                    arguments.len()
+                ),
+            ));
+        }
+
+        let mut named_values: Vec<(Token, Option<Value>)> = Vec::new();
+        for (name, value) in named {
+            let Some(index) = params[..positional]
+                .iter()
+                .position(|param| param.name.lexeme == name.lexeme)
+            else {
+                return Err(RuntimeError::new(
+                    name.clone(),
+                    format!("{} has no parameter named '{}'.", self, name.lexeme),
+                ));
+            };
+            let duplicated = named_values
+                .iter()
+                .any(|(other, _)| other.lexeme == name.lexeme);
+            if index < arguments.len() || duplicated {
+                return Err(RuntimeError::new(
+                    name.clone(),
+                    format!("Got multiple values for parameter '{}'.", name.lexeme),
+                ));
+            }
+            named_values.push((name, Some(value)));
+        }
+
+        let environment = Environment::new_enclosing(self.closure.clone());
+        let mut arguments = arguments.into_iter();
+        for param in params {
+            let value = if param.rest {
+                Value::List(Rc::new(RefCell::new(arguments.by_ref().collect())))
+            } else if let Some(argument) = arguments.next() {
+                argument
+            } else if let Some(value) = named_values
+                .iter_mut()
+                .find(|(name, _)| name.lexeme == param.name.lexeme)
+                .and_then(|(_, value)| value.take())
+            {
+                value
+            } else if let Some(default) = &param.default {
+                interpreter.evaluate_in(default, environment.clone())?
+            } else {
+                return Err(RuntimeError::new(
+                    paren.clone(),
+                    format!("Missing argument for parameter '{}'.", param.name.lexeme),
+                ));
+            };
+            environment.borrow_mut().define(&param.name, value)?;
+        }
+        Ok(environment)
+    }
+
+    // init 总是返回 this，即便是提前 return
+    fn this(&self) -> Result<Value, RuntimeError> {
+        let this = Token::new(
+            TokenType::THIS,
+            "this".to_string(),
+            None,
+            self.declaration.name.line,
+        );
+        self.closure.borrow().get_at(0, &this)
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    // 必须提供的参数个数
+    fn arity(&self) -> usize {
+        self.declaration
+            .params
+            .iter()
+            .filter(|param| param.default.is_none() && !param.rest)
+            .count()
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        self.call_with_named(interpreter, paren, arguments, Vec::new())
+    }
+
+    fn call_with_named(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Value>,
+        named: Vec<(Token, Value)>,
+    ) -> Result<Value, RuntimeError> {
+        // 参数默认值也可能调用自身，所以深度计数同时包住绑定参数和执行函数体
+        interpreter.enter_call(paren)?;
+        let result = match self.bind_arguments(interpreter, paren, arguments, named) {
+            Ok(environment) => interpreter.execute_block(&self.declaration.body, environment),
+            Err(e) => Err(Unwind::Error(e)),
+        };
+        interpreter.exit_call();
+        match result {
+            Ok(()) | Err(Unwind::Return(_)) if self.is_initializer => self.this(),
+            Ok(()) => Ok(Value::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(e)) => Err(e),
+            Err(Unwind::Break(_) | Unwind::Continue(_)) => {
+                unreachable!("Parser rejects break/continue outside of a loop.")
+            }
+        }
+    }
+}
+
+impl Display for LoxFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.declaration.is_anonymous() {
+            return write!(f, "<fn>");
+        }
+        write!(f, "<fn {}>", self.declaration.name.lexeme)
+    }
+}
+
+// 手动实现，避免打印整个函数体
+impl Debug for LoxFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::lox_class::LoxClass;
+use crate::token::Token;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub(crate) struct LoxInstance {
+    class: Rc<LoxClass>,
+    fields: HashMap<String, Value>,
+}
+
+impl LoxInstance {
+    pub(crate) fn new(class: Rc<LoxClass>) -> Self {
+        LoxInstance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn class(&self) -> &Rc<LoxClass> {
+        &self.class
+    }
+
+    pub(crate) fn class_name(&self) -> &str {
+        &self.class.name
+    }
+
+    pub(crate) fn has_property(&self, name: &str) -> bool {
+        self.fields.contains_key(name) || self.class.find_property(name).is_some()
+    }
+
+    pub(crate) fn field(&self, name: &str) -> Option<Value> {
+        self.fields.get(name).cloned()
+    }
+
+    pub(crate) fn set(&mut self, name: &Token, value: Value) {
+        self.fields.insert(name.lexeme.clone(), value);
+    }
+}
+
+impl Display for LoxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} instance", self.class_name())
+    }
+}
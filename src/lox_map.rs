@@ -0,0 +1,87 @@
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+// 可以作为 map 键的值。f64 没有实现 Eq/Hash，所以数字按规范化后的位模式存放：
+// -0.0 与 0.0 视为同一个键，所有 NaN 也视为同一个键（尽管 NaN == NaN 为假）。
+// 值为整数的浮点数存成整数键，这样 m[1] 与 m[1.0] 取到的是同一项
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum MapKey {
+    String(String),
+    Int(i64),
+    Number(u64),
+    Boolean(bool),
+    Nil,
+}
+
+impl MapKey {
+    pub(crate) fn from_value(value: &Value) -> Option<MapKey> {
+        match value {
+            Value::String(s) => Some(MapKey::String(s.clone())),
+            Value::Int(_) | Value::Number(_) if value.as_integer().is_some() => {
+                value.as_integer().map(MapKey::Int)
+            }
+            Value::Number(n) => Some(MapKey::Number(Self::normalize(*n).to_bits())),
+            Value::Boolean(b) => Some(MapKey::Boolean(*b)),
+            Value::Nil => Some(MapKey::Nil),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_value(&self) -> Value {
+        match self {
+            MapKey::String(s) => Value::String(s.clone()),
+            MapKey::Int(i) => Value::Int(*i),
+            MapKey::Number(bits) => Value::Number(f64::from_bits(*bits)),
+            MapKey::Boolean(b) => Value::Boolean(*b),
+            MapKey::Nil => Value::Nil,
+        }
+    }
+
+    fn normalize(n: f64) -> f64 {
+        if n.is_nan() {
+            f64::NAN
+        } else {
+            n
+        }
+    }
+}
+
+impl Display for MapKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_value())
+    }
+}
+
+// 按插入顺序遍历的 map，重新赋值不会改变键的位置
+#[derive(Debug, Default)]
+pub(crate) struct LoxMap {
+    entries: Vec<(MapKey, Value)>,
+    positions: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn get(&self, key: &MapKey) -> Option<Value> {
+        self.positions
+            .get(key)
+            .map(|position| self.entries[*position].1.clone())
+    }
+
+    pub(crate) fn set(&mut self, key: MapKey, value: Value) {
+        match self.positions.get(&key) {
+            Some(position) => self.entries[*position].1 = value,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &(MapKey, Value)> {
+        self.entries.iter()
+    }
+}
@@ -0,0 +1,49 @@
+use crate::environment::Environment;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+use crate::value::Value;
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::rc::Rc;
+
+// 执行完毕的模块：它自己的全局环境，以及其中可以被外部访问的顶层声明
+#[derive(Debug)]
+pub(crate) struct LoxModule {
+    pub(crate) name: String,
+    environment: Rc<RefCell<Environment>>,
+    pub(crate) exports: Vec<String>,
+}
+
+impl LoxModule {
+    pub(crate) fn new(
+        name: String,
+        environment: Rc<RefCell<Environment>>,
+        exports: Vec<String>,
+    ) -> Self {
+        LoxModule {
+            name,
+            environment,
+            exports,
+        }
+    }
+
+    pub(crate) fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
+        if !self.exports.contains(&name.lexeme) {
+            return Err(RuntimeError::new(
+                name.clone(),
+                format!("Module '{}' has no export '{}'.", self.name, name.lexeme),
+            ));
+        }
+        self.environment.borrow().get(name)
+    }
+
+    pub(crate) fn constant_line(&self, name: &str) -> Option<i32> {
+        self.environment.borrow().constant_line(name)
+    }
+}
+
+impl Display for LoxModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
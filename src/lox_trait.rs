@@ -0,0 +1,27 @@
+use crate::lox_class::ClassMembers;
+use crate::token::Token;
+use std::fmt::Display;
+
+// trait 只能被类混入，本身不能实例化；required 中的方法由混入它的类提供
+#[derive(Debug)]
+pub(crate) struct LoxTrait {
+    pub(crate) name: String,
+    pub(crate) members: ClassMembers,
+    pub(crate) required: Vec<Token>,
+}
+
+impl LoxTrait {
+    pub(crate) fn new(name: String, members: ClassMembers, required: Vec<Token>) -> Self {
+        LoxTrait {
+            name,
+            members,
+            required,
+        }
+    }
+}
+
+impl Display for LoxTrait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<trait {}>", self.name)
+    }
+}
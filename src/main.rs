@@ -2,15 +2,27 @@
 mod environment;
 mod expr;
 mod interpreter;
+mod lox_callable;
+mod lox_class;
+mod lox_enum;
+mod lox_function;
+mod lox_instance;
+mod lox_map;
+mod lox_module;
+mod lox_trait;
+mod native_function;
 mod parser;
+mod resolver;
 mod runtime_error;
 mod scanner;
 mod stmt;
 mod token;
 mod token_type;
+mod unwind;
 mod value;
 
 use crate::interpreter::Interpreter;
+use crate::resolver::Resolver;
 use once_cell::unsync::Lazy;
 use scanner::Scanner;
 use std::io::Write;
@@ -22,18 +34,26 @@ struct Lox {
     interpreter: Interpreter,
 }
 
-static mut LOX: Lazy<Lox> = Lazy::new(|| Lox::new());
+static mut LOX: Lazy<Lox> = Lazy::new(Lox::new);
+
+// 解释器是递归求值的，每层 Lox 调用会占用不少 Rust 栈。
+// 在栈足够大的线程上运行，使 interpreter 中的调用深度上限先于真正的栈溢出触发
+const STACK_SIZE: usize = 256 * 1024 * 1024;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    match args.len() {
-        1 => Lox::run_prompt().unwrap(),
-        2 => Lox::run_file(args[1].clone()).unwrap(),
-        _ => {
-            println!("Usage: rlox [script]");
-            std::process::exit(64);
-        }
-    }
+    let interpreter = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || match args.len() {
+            1 => Lox::run_prompt().unwrap(),
+            2 => Lox::run_file(args[1].clone()).unwrap(),
+            _ => {
+                println!("Usage: rlox [script]");
+                std::process::exit(64);
+            }
+        })
+        .unwrap();
+    interpreter.join().unwrap();
 }
 
 impl Lox {
@@ -44,8 +64,10 @@ impl Lox {
             interpreter: Interpreter::new(),
         }
     }
+    #[allow(static_mut_refs)]
     pub(crate) fn run_file(path: String) -> Result<(), std::io::Error> {
-        let source = std::fs::read_to_string(path)?;
+        let source = std::fs::read_to_string(&path)?;
+        unsafe { LOX.interpreter.set_script_path(path.into()) };
         Self::run(source);
         if unsafe { LOX.had_error } {
             std::process::exit(65);
@@ -69,6 +91,7 @@ impl Lox {
         }
     }
 
+    #[allow(static_mut_refs)]
     pub(crate) fn run(source: String) {
         let scanner = Scanner::new(source);
         let tokens = scanner.scan_tokens();
@@ -78,9 +101,19 @@ impl Lox {
             return;
         }
 
+        let mut resolver = Resolver::new(unsafe { &mut LOX.interpreter });
+        resolver.resolve(&statements);
+        if unsafe { LOX.had_error } {
+            return;
+        }
+
         unsafe { LOX.interpreter.interpret(statements) }
     }
 
+    pub(crate) fn had_error() -> bool {
+        unsafe { LOX.had_error }
+    }
+
     pub(crate) fn error_at_line(line: i32, message: String) {
         Self::report(line, "".to_string(), message);
     }
@@ -100,6 +133,14 @@ impl Lox {
         }
     }
 
+    // 警告只提示，不影响程序运行
+    pub(crate) fn warning(token: &Token, message: String) {
+        eprintln!(
+            "[line {}] Warning at '{}': {}",
+            token.line, token.lexeme, message
+        );
+    }
+
     pub(crate) fn runtime_error(error: runtime_error::RuntimeError) {
         eprintln!("{}\n[line {}]", error.message, error.token.line);
         unsafe {
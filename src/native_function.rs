@@ -0,0 +1,180 @@
+use crate::environment::Environment;
+use crate::interpreter::Interpreter;
+use crate::lox_callable::LoxCallable;
+use crate::runtime_error::RuntimeError;
+use crate::token::Token;
+use crate::value::Value;
+use std::cell::RefCell;
+use std::fmt::{Debug, Display};
+use std::io::Write;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// paren 是调用处的位置，用于报告错误；回调解释器时产生的错误（包括 throw 的值）原样传出
+type NativeFn = fn(&mut Interpreter, &Token, &[Value]) -> Result<Value, RuntimeError>;
+
+pub(crate) struct NativeFunction {
+    name: &'static str,
+    arity: usize,
+    function: NativeFn,
+}
+
+impl NativeFunction {
+    pub(crate) fn new(name: &'static str, arity: usize, function: NativeFn) -> Self {
+        NativeFunction {
+            name,
+            arity,
+            function,
+        }
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        (self.function)(interpreter, paren, &arguments)
+    }
+}
+
+impl Display for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+// 标准库：新的原生函数只需要在这里登记
+const NATIVES: &[(&str, usize, NativeFn)] = &[
+    ("clock", 0, clock),
+    ("len", 1, len),
+    ("range", 2, range),
+    ("str", 1, str),
+    ("typeof", 1, type_of),
+    ("write", 1, write),
+    ("writeln", 1, writeln),
+];
+
+pub(crate) fn define_natives(globals: &mut Environment) {
+    for (name, arity, function) in NATIVES {
+        let native = NativeFunction::new(name, *arity, *function);
+        globals.bind(name.to_string(), Value::Callable(Rc::new(native)));
+    }
+}
+
+fn clock(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    _arguments: &[Value],
+) -> Result<Value, RuntimeError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| RuntimeError::new(paren.clone(), e.to_string()))?;
+    Ok(Value::Number(now.as_secs_f64()))
+}
+
+fn len(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[Value],
+) -> Result<Value, RuntimeError> {
+    let len = match &arguments[0] {
+        Value::String(s) => s.chars().count(),
+        Value::List(elements) => elements.borrow().len(),
+        Value::Map(map) => map.borrow().entries().count(),
+        _ => {
+            return Err(RuntimeError::new(
+                paren.clone(),
+                "Can only take the length of strings, lists and maps.".to_string(),
+            ))
+        }
+    };
+    Ok(Value::Int(len as i64))
+}
+
+// range(start, end) 生成 [start, end) 内的整数列表，主要用于 for-in
+fn range(
+    _interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[Value],
+) -> Result<Value, RuntimeError> {
+    let (Value::Int(start), Value::Int(end)) = (&arguments[0], &arguments[1]) else {
+        return Err(RuntimeError::new(
+            paren.clone(),
+            "Range bounds must be integers.".to_string(),
+        ));
+    };
+    let elements = (*start..*end).map(Value::Int).collect();
+    Ok(Value::List(Rc::new(RefCell::new(elements))))
+}
+
+// 实例可以用 __str__ 自定义转换结果
+fn str(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[Value],
+) -> Result<Value, RuntimeError> {
+    let s = interpreter.stringify(paren, &arguments[0])?;
+    Ok(Value::String(s))
+}
+
+fn type_of(
+    _interpreter: &mut Interpreter,
+    _paren: &Token,
+    arguments: &[Value],
+) -> Result<Value, RuntimeError> {
+    let name = match &arguments[0] {
+        Value::Int(_) => "int".to_string(),
+        Value::Number(_) => "number".to_string(),
+        Value::Boolean(_) => "boolean".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::Callable(_) => "function".to_string(),
+        Value::Class(_) => "class".to_string(),
+        Value::Trait(_) => "trait".to_string(),
+        // 实例的类型就是它所属的类名
+        Value::Instance(instance) => instance.borrow().class_name().to_string(),
+        Value::List(_) => "list".to_string(),
+        Value::Map(_) => "map".to_string(),
+        Value::Module(_) => "module".to_string(),
+        Value::Enum(_) => "enum".to_string(),
+        // 与实例类似，enum 值的类型是它所属的 enum 名
+        Value::Variant(variant) => variant.lox_enum.name.clone(),
+        Value::Nil => "nil".to_string(),
+    };
+    Ok(Value::String(name))
+}
+
+fn write(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[Value],
+) -> Result<Value, RuntimeError> {
+    let s = interpreter.stringify(paren, &arguments[0])?;
+    print!("{}", s);
+    std::io::stdout()
+        .flush()
+        .map_err(|e| RuntimeError::new(paren.clone(), e.to_string()))?;
+    Ok(Value::Nil)
+}
+
+fn writeln(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    arguments: &[Value],
+) -> Result<Value, RuntimeError> {
+    let s = interpreter.stringify(paren, &arguments[0])?;
+    println!("{}", s);
+    Ok(Value::Nil)
+}
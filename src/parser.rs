@@ -1,18 +1,29 @@
-use crate::expr::Expr;
-use crate::stmt::Stmt;
+use crate::expr::{self, Argument, AssignPattern, Expr};
+use crate::stmt::{
+    CatchClause, ClassBody, Destructure, FunctionDecl, MatchArm, Param, Pattern, Stmt,
+};
 use crate::token::{Literal, Token};
 use crate::token_type::TokenType;
 use crate::token_type::TokenType::*;
 use crate::Lox;
+use std::rc::Rc;
+
+const MAX_ARGUMENTS: usize = 255;
 
 pub(crate) struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    // 当前所在的各层循环的标签（无标签为 None），用于检查 break/continue
+    loop_labels: Vec<Option<String>>,
 }
 
 impl Parser {
     pub(crate) fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            loop_labels: Vec::new(),
+        }
     }
 
     pub(crate) fn parse(&mut self) -> Vec<Stmt> {
@@ -26,19 +37,29 @@ impl Parser {
     }
 
     fn expression(&mut self) -> Result<Expr, ParseError> {
-        self.assignment()
+        self.comma()
     }
 
     fn declaration(&mut self) -> Option<Stmt> {
-        if self.match_token(&[VAR]) {
-            match self.var_declaration() {
-                Ok(stmt) => {
-                    return Some(stmt);
-                }
-                Err(_) => self.synchronize(),
-            }
-        }
-        match self.statement() {
+        let result = if self.match_token(&[CLASS]) {
+            self.class_declaration()
+        } else if self.match_token(&[TRAIT]) {
+            self.trait_declaration()
+        } else if self.match_token(&[ENUM]) {
+            self.enum_declaration()
+        } else if self.check(&FUN) && self.check_next(&IDENTIFIER) {
+            // 其余以 fun 开头的语句是匿名函数表达式语句
+            self.advance();
+            self.function("function")
+                .map(|declaration| Stmt::Function { declaration })
+        } else if self.match_token(&[VAR]) {
+            self.var_declaration()
+        } else if self.match_token(&[CONST]) {
+            self.const_declaration()
+        } else {
+            self.statement()
+        };
+        match result {
             Ok(stmt) => Some(stmt),
             Err(_) => {
                 self.synchronize();
@@ -52,31 +73,365 @@ impl Parser {
             return self.if_statement();
         }
         if self.match_token(&[PRINT]) {
-            return self.print_statement();
+            return self.print_statement(false);
         }
-        if self.match_token(&[LEFT_BRACE]) {
+        if self.match_token(&[RETURN]) {
+            return self.return_statement(false);
+        }
+        // {x, y} = e; 是解构赋值而不是语句块
+        if !self.destructure_assignment_ahead() && self.match_token(&[LEFT_BRACE]) {
             return Ok(Stmt::Block {
                 statements: self.block()?,
             });
         }
         if self.match_token(&[WHILE]) {
-            return self.while_statement();
+            return self.while_statement(None);
+        }
+        if self.match_token(&[FOR]) {
+            return self.for_statement(None);
+        }
+        if self.match_token(&[IMPORT]) {
+            return self.import_statement();
+        }
+        if self.match_token(&[THROW]) {
+            return self.throw_statement(false);
+        }
+        if self.match_token(&[TRY]) {
+            return self.try_statement();
+        }
+        if self.match_token(&[MATCH]) {
+            return self.match_statement();
+        }
+        if self.match_token(&[BREAK]) {
+            return self.break_statement(false);
+        }
+        if self.match_token(&[CONTINUE]) {
+            return self.continue_statement(false);
+        }
+        if self.check(&IDENTIFIER) && self.check_next(&COLON) {
+            return self.labeled_statement();
+        }
+        self.expression_statement(false)
+    }
+
+    fn labeled_statement(&mut self) -> Result<Stmt, ParseError> {
+        let label = self.advance();
+        self.advance();
+        if self.loop_labels.contains(&Some(label.lexeme.clone())) {
+            Self::error(
+                label.clone(),
+                format!("Label '{}' is already in use.", label.lexeme),
+            );
+        }
+        if self.match_token(&[WHILE]) {
+            return self.while_statement(Some(label));
         }
         if self.match_token(&[FOR]) {
-            return self.for_statement();
+            return self.for_statement(Some(label));
+        }
+        Err(Self::error(
+            self.peek(),
+            "Expect loop after label.".to_string(),
+        ))
+    }
+
+    fn import_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let path = self.consume(STRING, "Expect module path after 'import'.".to_string())?;
+        let mut alias = None;
+        // as 不是保留字，只在这里有特殊含义
+        if self.check(&IDENTIFIER) && self.peek().lexeme == "as" {
+            self.advance();
+            alias = Some(self.consume(IDENTIFIER, "Expect module name after 'as'.".to_string())?);
+        }
+        self.consume(SEMICOLON, "Expect ';' after import.".to_string())?;
+        Ok(Stmt::Import {
+            keyword,
+            path,
+            alias,
+        })
+    }
+
+    fn throw_statement(&mut self, in_arm: bool) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let value = Box::new(self.statement_expression(in_arm)?);
+        self.end_statement(in_arm, "Expect ';' after thrown value.")?;
+        Ok(Stmt::Throw { keyword, value })
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        self.consume(LEFT_BRACE, "Expect '{' after 'try'.".to_string())?;
+        let body = self.block()?;
+
+        let mut catch = None;
+        if self.match_token(&[CATCH]) {
+            self.consume(LEFT_PAREN, "Expect '(' after 'catch'.".to_string())?;
+            let name = self.consume(IDENTIFIER, "Expect exception variable name.".to_string())?;
+            self.consume(
+                RIGHT_PAREN,
+                "Expect ')' after exception variable.".to_string(),
+            )?;
+            self.consume(LEFT_BRACE, "Expect '{' after catch clause.".to_string())?;
+            catch = Some(CatchClause {
+                name,
+                body: self.block()?,
+            });
+        }
+
+        let mut finally = None;
+        if self.match_token(&[FINALLY]) {
+            self.consume(LEFT_BRACE, "Expect '{' after 'finally'.".to_string())?;
+            finally = Some(self.block()?);
+        }
+
+        if catch.is_none() && finally.is_none() {
+            return Err(Self::error(
+                keyword,
+                "Expect 'catch' or 'finally' after try block.".to_string(),
+            ));
+        }
+        Ok(Stmt::Try {
+            body,
+            catch,
+            finally,
+        })
+    }
+
+    fn match_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        self.consume(LEFT_PAREN, "Expect '(' after 'match'.".to_string())?;
+        let subject = Box::new(self.expression()?);
+        self.consume(RIGHT_PAREN, "Expect ')' after match value.".to_string())?;
+        self.consume(LEFT_BRACE, "Expect '{' before match arms.".to_string())?;
+
+        let mut arms = Vec::new();
+        let mut exhausted = false;
+        while !self.check(&RIGHT_BRACE) && !self.is_at_end() {
+            let pattern = self.pattern()?;
+            let guard = if self.match_token(&[IF]) {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+            self.consume(FAT_ARROW, "Expect '=>' after pattern.".to_string())?;
+            let body = self.match_arm_body()?;
+            self.match_token(&[COMMA]);
+
+            if exhausted {
+                Lox::warning(pattern.token(), "Unreachable match arm.".to_string());
+            }
+            if guard.is_none() && pattern.is_irrefutable() {
+                exhausted = true;
+            }
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+        }
+        self.consume(RIGHT_BRACE, "Expect '}' after match arms.".to_string())?;
+
+        Ok(Stmt::Match {
+            keyword,
+            subject,
+            arms,
+        })
+    }
+
+    // 分支体是简单语句时可以省略结尾的 ';'，见 end_statement
+    fn match_arm_body(&mut self) -> Result<Stmt, ParseError> {
+        if self.match_token(&[PRINT]) {
+            return self.print_statement(true);
+        }
+        if self.match_token(&[RETURN]) {
+            return self.return_statement(true);
         }
-        self.expression_statement()
+        if self.match_token(&[THROW]) {
+            return self.throw_statement(true);
+        }
+        if self.match_token(&[BREAK]) {
+            return self.break_statement(true);
+        }
+        if self.match_token(&[CONTINUE]) {
+            return self.continue_statement(true);
+        }
+        let compound = matches!(
+            self.peek().token_type,
+            IF | LEFT_BRACE | WHILE | FOR | IMPORT | TRY | MATCH
+        ) && !self.destructure_assignment_ahead()
+            || (self.check(&IDENTIFIER) && self.check_next(&COLON));
+        if compound {
+            return self.statement();
+        }
+        self.expression_statement(true)
+    }
+
+    // 简单语句中的表达式；在 match 分支中不解析逗号运算符，否则下一个分支会被当成它的操作数
+    fn statement_expression(&mut self, in_arm: bool) -> Result<Expr, ParseError> {
+        if in_arm {
+            self.assignment()
+        } else {
+            self.expression()
+        }
+    }
+
+    // 简单语句结尾的 ';'；match 分支中的语句也可以停在分隔分支的 ',' 或结束 match 的 '}'，
+    // 它们由 match_statement 消费
+    fn end_statement(&mut self, in_arm: bool, message: &str) -> Result<(), ParseError> {
+        if !self.ends_match_arm(in_arm) {
+            self.consume(SEMICOLON, message.to_string())?;
+        }
+        Ok(())
     }
 
-    fn print_statement(&mut self) -> Result<Stmt, ParseError> {
-        let value = self.expression()?;
-        self.consume(SEMICOLON, "Expect ';' after value.".to_string())?;
+    fn ends_match_arm(&self, in_arm: bool) -> bool {
+        in_arm && (self.check(&COMMA) || self.check(&RIGHT_BRACE))
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, ParseError> {
+        if self.match_token(&[IDENTIFIER]) {
+            let name = self.previous();
+            if name.lexeme == "_" {
+                return Ok(Pattern::Wildcard { token: name });
+            }
+            if self.match_token(&[DOT]) {
+                return self.variant_pattern(name);
+            }
+            return Ok(Pattern::Binding { name });
+        }
+
+        let token = self.peek();
+        let start = self.pattern_literal()?;
+        if self.match_token(&[DOT_DOT, DOT_DOT_EQUAL]) {
+            let inclusive = self.previous().token_type == DOT_DOT_EQUAL;
+            let end = self.pattern_literal()?;
+            return Ok(Pattern::Range {
+                token,
+                start,
+                end,
+                inclusive,
+            });
+        }
+        Ok(Pattern::Literal {
+            token,
+            value: start,
+        })
+    }
+
+    fn variant_pattern(&mut self, enum_name: Token) -> Result<Pattern, ParseError> {
+        let variant = self.consume(IDENTIFIER, "Expect variant name after '.'.".to_string())?;
+        let mut fields = None;
+        if self.match_token(&[LEFT_PAREN]) {
+            let mut patterns = Vec::new();
+            if !self.check(&RIGHT_PAREN) {
+                loop {
+                    patterns.push(self.pattern()?);
+                    if !self.match_token(&[COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(
+                RIGHT_PAREN,
+                "Expect ')' after variant patterns.".to_string(),
+            )?;
+            fields = Some(patterns);
+        }
+        Ok(Pattern::Variant {
+            id: expr::next_id(),
+            enum_name,
+            variant,
+            fields,
+        })
+    }
+
+    // 模式中的字面量，数字前可以带负号
+    fn pattern_literal(&mut self) -> Result<Literal, ParseError> {
+        if self.match_token(&[MINUS]) {
+            let number = self.consume(NUMBER, "Expect number after '-' in pattern.".to_string())?;
+            return Ok(match number.literal {
+                Some(Literal::Int(i)) => Literal::Int(-i),
+                Some(Literal::Number(n)) => Literal::Number(-n),
+                _ => unreachable!("Number tokens always carry a number literal."),
+            });
+        }
+        if self.match_token(&[NUMBER, STRING, TRUE, FALSE, NIL]) {
+            return Ok(self.previous().literal.unwrap());
+        }
+        Err(Self::error(self.peek(), "Expect pattern.".to_string()))
+    }
+
+    fn break_statement(&mut self, in_arm: bool) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let label = self.loop_jump_label(&keyword)?;
+        self.end_statement(in_arm, "Expect ';' after 'break'.")?;
+        Ok(Stmt::Break { keyword, label })
+    }
+
+    fn continue_statement(&mut self, in_arm: bool) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let label = self.loop_jump_label(&keyword)?;
+        self.end_statement(in_arm, "Expect ';' after 'continue'.")?;
+        Ok(Stmt::Continue { keyword, label })
+    }
+
+    // 解析 break/continue 后可选的标签，并检查它们确实位于（对应标签的）循环内
+    fn loop_jump_label(&mut self, keyword: &Token) -> Result<Option<Token>, ParseError> {
+        let label = if self.match_token(&[IDENTIFIER]) {
+            Some(self.previous())
+        } else {
+            None
+        };
+        if self.loop_labels.is_empty() {
+            Self::error(
+                keyword.clone(),
+                format!("Can't use '{}' outside of a loop.", keyword.lexeme),
+            );
+        } else if let Some(label) = &label {
+            if !self.loop_labels.contains(&Some(label.lexeme.clone())) {
+                Self::error(
+                    label.clone(),
+                    format!("Undefined loop label '{}'.", label.lexeme),
+                );
+            }
+        }
+        Ok(label)
+    }
+
+    fn loop_body(&mut self, label: &Option<Token>) -> Result<Stmt, ParseError> {
+        self.loop_labels
+            .push(label.as_ref().map(|label| label.lexeme.clone()));
+        let body = self.statement();
+        self.loop_labels.pop();
+        body
+    }
+
+    fn print_statement(&mut self, in_arm: bool) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let value = self.statement_expression(in_arm)?;
+        self.end_statement(in_arm, "Expect ';' after value.")?;
         Ok(Stmt::Print {
+            keyword,
             expression: Box::new(value),
         })
     }
 
+    fn return_statement(&mut self, in_arm: bool) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let value = if !self.check(&SEMICOLON) && !self.ends_match_arm(in_arm) {
+            Some(Box::new(self.statement_expression(in_arm)?))
+        } else {
+            None
+        };
+        self.end_statement(in_arm, "Expect ';' after return value.")?;
+        Ok(Stmt::Return { keyword, value })
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        if self.match_token(&[LEFT_BRACKET, LEFT_BRACE]) {
+            return self.destructure_declaration();
+        }
         let name: Token = self.consume(IDENTIFIER, "Expect variable name.".to_string())?;
         let mut initializer = None;
         if self.match_token(&[EQUAL]) {
@@ -89,26 +444,123 @@ impl Parser {
         Ok(Stmt::Var { name, initializer })
     }
 
-    fn while_statement(&mut self) -> Result<Stmt, ParseError> {
+    fn const_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(IDENTIFIER, "Expect constant name.".to_string())?;
+        if !self.match_token(&[EQUAL]) {
+            return Err(Self::error(
+                name,
+                "Constant must be initialized.".to_string(),
+            ));
+        }
+        let initializer = Box::new(self.expression()?);
+        self.consume(
+            SEMICOLON,
+            "Expect ';' after constant declaration.".to_string(),
+        )?;
+        Ok(Stmt::Const { name, initializer })
+    }
+
+    fn destructure_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let open = self.previous();
+        let pattern = if open.token_type == LEFT_BRACKET {
+            let mut names = Vec::new();
+            let mut rest = None;
+            if !self.check(&RIGHT_BRACKET) {
+                loop {
+                    if self.match_token(&[DOT_DOT_DOT]) {
+                        rest =
+                            Some(self.consume(IDENTIFIER, "Expect name after '...'.".to_string())?);
+                        break;
+                    }
+                    names.push(self.consume(IDENTIFIER, "Expect variable name.".to_string())?);
+                    if !self.match_token(&[COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(
+                RIGHT_BRACKET,
+                "Expect ']' after destructuring pattern.".to_string(),
+            )?;
+            Destructure::List {
+                bracket: open,
+                names,
+                rest,
+            }
+        } else {
+            let mut names = Vec::new();
+            if !self.check(&RIGHT_BRACE) {
+                loop {
+                    names.push(self.consume(IDENTIFIER, "Expect property name.".to_string())?);
+                    if !self.match_token(&[COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(
+                RIGHT_BRACE,
+                "Expect '}' after destructuring pattern.".to_string(),
+            )?;
+            Destructure::Object { brace: open, names }
+        };
+
+        self.consume(EQUAL, "Expect '=' after destructuring pattern.".to_string())?;
+        let initializer = Box::new(self.expression()?);
+        self.consume(
+            SEMICOLON,
+            "Expect ';' after variable declaration.".to_string(),
+        )?;
+        Ok(Stmt::Destructure {
+            pattern,
+            initializer,
+        })
+    }
+
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
         self.consume(LEFT_PAREN, "Expect '(' after 'while'.".to_string())?;
         let condition = Box::new(self.expression()?);
         self.consume(RIGHT_PAREN, "Expect ')' after condition.".to_string())?;
-        let body = Box::new(self.statement()?);
+        let body = Box::new(self.loop_body(&label)?);
 
-        Ok(Stmt::While { condition, body })
+        Ok(Stmt::While {
+            condition,
+            body,
+            increment: None,
+            label,
+        })
     }
 
-    fn for_statement(&mut self) -> Result<Stmt, ParseError> {
+    fn for_in_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
+        let name = self.consume(IDENTIFIER, "Expect loop variable name.".to_string())?;
+        let keyword = self.consume(IN, "Expect 'in' after loop variable.".to_string())?;
+        let iterable = Box::new(self.expression()?);
+        self.consume(RIGHT_PAREN, "Expect ')' after for-in clause.".to_string())?;
+        let body = Box::new(self.loop_body(&label)?);
+
+        Ok(Stmt::ForIn {
+            name,
+            keyword,
+            iterable,
+            body,
+            label,
+        })
+    }
+
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
         // start reading the for loop header
 
         self.consume(LEFT_PAREN, "Expect '(' after 'for'.".to_string())?;
 
+        if self.check(&IDENTIFIER) && self.check_next(&IN) {
+            return self.for_in_statement(label);
+        }
+
         let initializer = if self.match_token(&[SEMICOLON]) {
             None
         } else if self.match_token(&[VAR]) {
             Some(self.var_declaration()?)
         } else {
-            Some(self.expression_statement()?)
+            Some(self.expression_statement(false)?)
         };
 
         let condition = if !self.check(&SEMICOLON) {
@@ -129,31 +581,25 @@ impl Parser {
 
         // reading the for loop body
 
-        let mut body = Box::new(self.statement()?);
+        let mut body = Box::new(self.loop_body(&label)?);
 
         // desugar
 
-        if let Some(increment) = increment {
-            body = Box::new(Stmt::Block {
-                statements: vec![
-                    *body,
-                    Stmt::Expression {
-                        expression: Box::new(increment),
-                    },
-                ],
-            });
-        }
-
         /* Original:
+            if (increment != null) body = new Stmt.Block(Arrays.asList(body, new Stmt.Expression(increment)));
             if (condition == null) condition = new Expr.Literal(true);
             body = new Stmt.While(condition, body);
+           The increment is kept on the while statement instead of being appended to the body,
+           so that `continue` still runs it.
         */
 
         body = Box::new(Stmt::While {
-            condition: Box::new(condition.unwrap_or_else(|| Expr::Literal {
+            condition: Box::new(condition.unwrap_or(Expr::Literal {
                 value: Literal::Bool(true),
             })),
             body,
+            increment: increment.map(Box::new),
+            label,
         });
 
         // if there is an initializer, wrap the body in a block with the initializer
@@ -166,14 +612,280 @@ impl Parser {
         Ok(*body)
     }
 
-    fn expression_statement(&mut self) -> Result<Stmt, ParseError> {
-        let expr = self.expression()?;
-        self.consume(SEMICOLON, "Expect ';' after expression.".to_string())?;
+    fn expression_statement(&mut self, in_arm: bool) -> Result<Stmt, ParseError> {
+        let expr = self.statement_expression(in_arm)?;
+        self.end_statement(in_arm, "Expect ';' after expression.")?;
         Ok(Stmt::Expression {
             expression: Box::new(expr),
         })
     }
 
+    fn enum_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(IDENTIFIER, "Expect enum name.".to_string())?;
+        self.consume(LEFT_BRACE, "Expect '{' before enum body.".to_string())?;
+
+        let mut variants: Vec<(Token, Vec<Token>)> = Vec::new();
+        while !self.check(&RIGHT_BRACE) && !self.is_at_end() {
+            let variant = self.consume(IDENTIFIER, "Expect variant name.".to_string())?;
+            if variants
+                .iter()
+                .any(|(other, _)| other.lexeme == variant.lexeme)
+            {
+                Lox::error_at_token(
+                    variant.clone(),
+                    "Already a variant with this name in this enum.".to_string(),
+                );
+            }
+            let mut fields = Vec::new();
+            if self.match_token(&[LEFT_PAREN]) {
+                if !self.check(&RIGHT_PAREN) {
+                    loop {
+                        fields.push(self.consume(IDENTIFIER, "Expect field name.".to_string())?);
+                        if !self.match_token(&[COMMA]) {
+                            break;
+                        }
+                    }
+                }
+                self.consume(RIGHT_PAREN, "Expect ')' after variant fields.".to_string())?;
+            }
+            variants.push((variant, fields));
+            if !self.match_token(&[COMMA]) {
+                break;
+            }
+        }
+
+        self.consume(RIGHT_BRACE, "Expect '}' after enum body.".to_string())?;
+        Ok(Stmt::Enum { name, variants })
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(IDENTIFIER, "Expect class name.".to_string())?;
+
+        let mut superclass = None;
+        if self.match_token(&[LESS]) {
+            self.consume(IDENTIFIER, "Expect superclass name.".to_string())?;
+            superclass = Some(Box::new(Expr::Variable {
+                id: expr::next_id(),
+                name: self.previous(),
+            }));
+        }
+
+        let mut traits = Vec::new();
+        if self.match_token(&[WITH]) {
+            loop {
+                self.consume(IDENTIFIER, "Expect trait name.".to_string())?;
+                traits.push(Expr::Variable {
+                    id: expr::next_id(),
+                    name: self.previous(),
+                });
+                if !self.match_token(&[COMMA]) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(LEFT_BRACE, "Expect '{' before class body.".to_string())?;
+        let body = self.class_body("class", None)?;
+        Ok(Stmt::Class {
+            name,
+            superclass,
+            traits,
+            body,
+        })
+    }
+
+    fn trait_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(IDENTIFIER, "Expect trait name.".to_string())?;
+        self.consume(LEFT_BRACE, "Expect '{' before trait body.".to_string())?;
+        let mut required = Vec::new();
+        let body = self.class_body("trait", Some(&mut required))?;
+        Ok(Stmt::Trait {
+            name,
+            body,
+            required,
+        })
+    }
+
+    // 解析类或 trait 的成员直到 '}'；只有 trait 传入 required，此时以 ';' 结尾的方法签名
+    // 记为必须由类实现的方法
+    fn class_body(
+        &mut self,
+        kind: &str,
+        mut required: Option<&mut Vec<Token>>,
+    ) -> Result<ClassBody, ParseError> {
+        let mut body = ClassBody::default();
+        while !self.check(&RIGHT_BRACE) && !self.is_at_end() {
+            if self
+                .class_member(&mut body, required.as_deref_mut())
+                .is_err()
+            {
+                self.synchronize_member();
+            }
+        }
+        Self::check_member_names(&body);
+
+        self.consume(RIGHT_BRACE, format!("Expect '}}' after {} body.", kind))?;
+        Ok(body)
+    }
+
+    // 读写属性时先找 getter/setter 再找方法，同名的方法会被遮住；init 只能是普通方法
+    fn check_member_names(body: &ClassBody) {
+        for (kind, accessors) in [("getter", &body.getters), ("setter", &body.setters)] {
+            for accessor in accessors {
+                let name = &accessor.name;
+                if name.lexeme == "init" {
+                    Self::error(name.clone(), format!("A {} can't be named 'init'.", kind));
+                } else if body
+                    .methods
+                    .iter()
+                    .any(|method| method.name.lexeme == name.lexeme)
+                {
+                    Self::error(
+                        name.clone(),
+                        format!("A {} can't have the same name as a method.", kind),
+                    );
+                }
+            }
+        }
+    }
+
+    fn class_member(
+        &mut self,
+        body: &mut ClassBody,
+        required: Option<&mut Vec<Token>>,
+    ) -> Result<(), ParseError> {
+        if self.match_token(&[CLASS]) {
+            body.class_methods.push(self.function("method")?);
+        } else if self.check(&IDENTIFIER)
+            && self.peek().lexeme == "set"
+            && self.check_next(&IDENTIFIER)
+        {
+            // set 只在后面紧跟属性名时才表示 setter，set(...) 仍是普通方法
+            self.advance();
+            let setter = self.function("setter")?;
+            if setter.params.len() != 1 || setter.params[0].rest {
+                Self::error(
+                    setter.name.clone(),
+                    "A setter must have exactly one parameter.".to_string(),
+                );
+            }
+            body.setters.push(setter);
+        } else if self.check(&IDENTIFIER) && self.check_next(&LEFT_BRACE) {
+            let name = self.advance();
+            let statements = self.function_block("getter")?;
+            body.getters.push(Rc::new(FunctionDecl {
+                name,
+                params: Vec::new(),
+                body: statements,
+            }));
+        } else if let Some(required) = required {
+            let name = self.consume(IDENTIFIER, "Expect method name.".to_string())?;
+            self.consume(LEFT_PAREN, "Expect '(' after method name.".to_string())?;
+            let params = self.parameters()?;
+            if self.match_token(&[SEMICOLON]) {
+                required.push(name);
+            } else {
+                let statements = self.function_block("method")?;
+                body.methods.push(Rc::new(FunctionDecl {
+                    name,
+                    params,
+                    body: statements,
+                }));
+            }
+        } else {
+            body.methods.push(self.function("method")?);
+        }
+        Ok(())
+    }
+
+    // 成员解析出错后跳到下一个成员：越过出错成员结尾的 ';' 或整个方法体，
+    // 停在类体的 '}' 之前，以免它被当成类体之后的语句
+    fn synchronize_member(&mut self) {
+        while !self.is_at_end() {
+            match self.peek().token_type {
+                SEMICOLON => {
+                    self.advance();
+                    return;
+                }
+                RIGHT_BRACE => return,
+                LEFT_BRACE => {
+                    let mut depth = 0;
+                    while !self.is_at_end() {
+                        match self.advance().token_type {
+                            LEFT_BRACE => depth += 1,
+                            RIGHT_BRACE => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 {
+                            return;
+                        }
+                    }
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    fn function(&mut self, kind: &str) -> Result<Rc<FunctionDecl>, ParseError> {
+        let name = self.consume(IDENTIFIER, format!("Expect {} name.", kind))?;
+        self.consume(LEFT_PAREN, format!("Expect '(' after {} name.", kind))?;
+        self.function_body(name, kind)
+    }
+
+    // 从参数列表开始解析函数的剩余部分，具名函数、方法和匿名函数共用
+    fn function_body(&mut self, name: Token, kind: &str) -> Result<Rc<FunctionDecl>, ParseError> {
+        let params = self.parameters()?;
+        let body = self.function_block(kind)?;
+        Ok(Rc::new(FunctionDecl { name, params, body }))
+    }
+
+    // 解析 '(' 之后的参数列表，直到并包括 ')'
+    fn parameters(&mut self) -> Result<Vec<Param>, ParseError> {
+        let mut params = Vec::new();
+        if !self.check(&RIGHT_PAREN) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    // 只报告错误，不需要进入同步模式
+                    Self::error(
+                        self.peek(),
+                        format!("Can't have more than {} parameters.", MAX_ARGUMENTS),
+                    );
+                }
+                if params.last().is_some_and(|param: &Param| param.rest) {
+                    Self::error(self.previous(), "Rest parameter must be last.".to_string());
+                }
+                let rest = self.match_token(&[DOT_DOT_DOT]);
+                let name = self.consume(IDENTIFIER, "Expect parameter name.".to_string())?;
+                let default = if !rest && self.match_token(&[EQUAL]) {
+                    Some(self.assignment()?)
+                } else {
+                    None
+                };
+                params.push(Param {
+                    name,
+                    default,
+                    rest,
+                });
+                if !self.match_token(&[COMMA]) {
+                    break;
+                }
+            }
+        }
+        self.consume(RIGHT_PAREN, "Expect ')' after parameters.".to_string())?;
+        Ok(params)
+    }
+
+    fn function_block(&mut self, kind: &str) -> Result<Vec<Stmt>, ParseError> {
+        self.consume(LEFT_BRACE, format!("Expect '{{' before {} body.", kind))?;
+        // 函数体内不能 break/continue 到函数外的循环
+        let enclosing_loops = std::mem::take(&mut self.loop_labels);
+        let body = self.block();
+        self.loop_labels = enclosing_loops;
+        body
+    }
+
     fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
         let mut statements = Vec::new();
         while !self.check(&RIGHT_BRACE) && !self.is_at_end() {
@@ -185,16 +897,73 @@ impl Parser {
         Ok(statements)
     }
 
+    // 逗号运算符优先级最低；参数列表、列表和 map 字面量中的元素直接从 assignment 开始解析，
+    // 因此那里的逗号仍然是分隔符
+    fn comma(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.assignment()?;
+        while self.match_token(&[COMMA]) {
+            let operator = self.previous();
+            let right = self.assignment()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+        Ok(expr)
+    }
+
     fn assignment(&mut self) -> Result<Expr, ParseError> {
-        let expr = self.or()?;
+        if self.destructure_assignment_ahead() {
+            return self.destructure_assignment();
+        }
+        let expr = self.conditional()?;
+        if self.match_token(&[PLUS_EQUAL, MINUS_EQUAL, STAR_EQUAL, SLASH_EQUAL]) {
+            let operator = self.previous();
+            let value = self.assignment()?;
+            if !expr.is_assignable() {
+                return Err(Self::error(
+                    operator,
+                    "Invalid assignment target.".to_string(),
+                ));
+            }
+            return Ok(Expr::CompoundAssign {
+                target: Box::new(expr),
+                operator,
+                value: Box::new(value),
+            });
+        }
         if self.match_token(&[EQUAL]) {
             let equals = self.previous();
             let value = self.assignment()?;
-            if let Expr::Variable { name } = expr {
-                return Ok(Expr::Assign {
-                    name,
-                    value: Box::new(value),
-                });
+            match expr {
+                Expr::Variable { name, .. } => {
+                    return Ok(Expr::Assign {
+                        id: expr::next_id(),
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                Expr::Get { object, name } => {
+                    return Ok(Expr::Set {
+                        object,
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                Expr::Index {
+                    object,
+                    bracket,
+                    index,
+                } => {
+                    return Ok(Expr::IndexSet {
+                        object,
+                        bracket,
+                        index,
+                        value: Box::new(value),
+                    });
+                }
+                _ => {}
             }
             return Err(Self::error(
                 equals,
@@ -204,6 +973,130 @@ impl Parser {
         Ok(expr)
     }
 
+    // 从 '[' 或 '{' 开始、匹配的右括号后紧跟 '=' 的是解构赋值。'{' 开头的只可能是
+    // {x, y} 这种形式，直接按 token 判断，避免对每个语句块都向后扫描
+    fn destructure_assignment_ahead(&self) -> bool {
+        if self.check(&LEFT_BRACE) {
+            let mut position = self.current + 1;
+            loop {
+                match self.tokens[position].token_type {
+                    IDENTIFIER => position += 1,
+                    RIGHT_BRACE => break,
+                    _ => return false,
+                }
+                match self.tokens[position].token_type {
+                    COMMA => position += 1,
+                    RIGHT_BRACE => break,
+                    _ => return false,
+                }
+            }
+            return self.tokens[position + 1].token_type == EQUAL;
+        }
+        if !self.check(&LEFT_BRACKET) {
+            return false;
+        }
+        let mut depth = 0;
+        for (position, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.token_type {
+                LEFT_BRACKET | LEFT_BRACE | LEFT_PAREN => depth += 1,
+                RIGHT_BRACKET | RIGHT_BRACE | RIGHT_PAREN => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self.tokens[position + 1].token_type == EQUAL;
+                    }
+                }
+                EOF => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn destructure_assignment(&mut self) -> Result<Expr, ParseError> {
+        let open = self.advance();
+        let pattern = if open.token_type == LEFT_BRACKET {
+            let mut targets = Vec::new();
+            let mut rest = None;
+            if !self.check(&RIGHT_BRACKET) {
+                loop {
+                    if self.match_token(&[DOT_DOT_DOT]) {
+                        rest = Some(Box::new(self.assignment_target()?));
+                        break;
+                    }
+                    targets.push(self.assignment_target()?);
+                    if !self.match_token(&[COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(
+                RIGHT_BRACKET,
+                "Expect ']' after destructuring pattern.".to_string(),
+            )?;
+            AssignPattern::List {
+                bracket: open,
+                targets,
+                rest,
+            }
+        } else {
+            let mut names = Vec::new();
+            if !self.check(&RIGHT_BRACE) {
+                loop {
+                    let name = self.consume(IDENTIFIER, "Expect property name.".to_string())?;
+                    names.push(Expr::Variable {
+                        id: expr::next_id(),
+                        name,
+                    });
+                    if !self.match_token(&[COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(
+                RIGHT_BRACE,
+                "Expect '}' after destructuring pattern.".to_string(),
+            )?;
+            AssignPattern::Object { brace: open, names }
+        };
+
+        let equals = self.consume(EQUAL, "Expect '=' after destructuring pattern.".to_string())?;
+        let value = self.assignment()?;
+        Ok(Expr::DestructureAssign {
+            pattern,
+            equals,
+            value: Box::new(value),
+        })
+    }
+
+    fn assignment_target(&mut self) -> Result<Expr, ParseError> {
+        let token = self.peek();
+        let target = self.conditional()?;
+        if !target.is_assignable() {
+            // 只报告错误，不需要进入同步模式
+            Self::error(token, "Invalid assignment target.".to_string());
+        }
+        Ok(target)
+    }
+
+    // 右结合：a ? b : c ? d : e 等价于 a ? b : (c ? d : e)
+    fn conditional(&mut self) -> Result<Expr, ParseError> {
+        let condition = self.or()?;
+        if self.match_token(&[QUESTION]) {
+            let then_branch = self.expression()?;
+            self.consume(
+                COLON,
+                "Expect ':' after then branch of conditional expression.".to_string(),
+            )?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            });
+        }
+        Ok(condition)
+    }
+
     fn or(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.and();
 
@@ -278,7 +1171,7 @@ impl Parser {
 
     fn factor(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.unary();
-        while self.match_token(&[SLASH, STAR]) {
+        while self.match_token(&[SLASH, STAR, PERCENT, TILDE_SLASH]) {
             let operator = self.previous();
             let right = self.unary();
             expr = Ok(Expr::Binary {
@@ -299,7 +1192,113 @@ impl Parser {
                 right: Box::new(right),
             });
         }
-        self.primary()
+        if self.match_token(&[PLUS_PLUS, MINUS_MINUS]) {
+            let operator = self.previous();
+            let target = self.unary()?;
+            return Self::increment(target, operator, false);
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.call()?;
+        if self.match_token(&[PLUS_PLUS, MINUS_MINUS]) {
+            let operator = self.previous();
+            return Self::increment(expr, operator, true);
+        }
+        Ok(expr)
+    }
+
+    fn increment(target: Expr, operator: Token, postfix: bool) -> Result<Expr, ParseError> {
+        if !target.is_assignable() {
+            return Err(Self::error(
+                operator.clone(),
+                format!("Invalid operand for '{}'.", operator.lexeme),
+            ));
+        }
+        Ok(Expr::Increment {
+            target: Box::new(target),
+            operator,
+            postfix,
+        })
+    }
+
+    fn call(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.match_token(&[LEFT_PAREN]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_token(&[DOT]) {
+                let name =
+                    self.consume(IDENTIFIER, "Expect property name after '.'.".to_string())?;
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                };
+            } else if self.match_token(&[LEFT_BRACKET]) {
+                let index = self.expression()?;
+                let bracket = self.consume(RIGHT_BRACKET, "Expect ']' after index.".to_string())?;
+                expr = Expr::Index {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                };
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let mut arguments = Vec::new();
+        if !self.check(&RIGHT_PAREN) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    Self::error(
+                        self.peek(),
+                        format!("Can't have more than {} arguments.", MAX_ARGUMENTS),
+                    );
+                }
+                arguments.push(self.argument()?);
+                let is_named = matches!(arguments.last(), Some(Argument::Named { .. }));
+                if !is_named
+                    && arguments
+                        .iter()
+                        .any(|a| matches!(a, Argument::Named { .. }))
+                {
+                    Self::error(
+                        self.previous(),
+                        "Positional arguments must come before named arguments.".to_string(),
+                    );
+                }
+                if !self.match_token(&[COMMA]) {
+                    break;
+                }
+            }
+        }
+        let paren = self.consume(RIGHT_PAREN, "Expect ')' after arguments.".to_string())?;
+        Ok(Expr::Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        })
+    }
+
+    fn argument(&mut self) -> Result<Argument, ParseError> {
+        if self.match_token(&[DOT_DOT_DOT]) {
+            let ellipsis = self.previous();
+            let value = self.assignment()?;
+            return Ok(Argument::Spread { ellipsis, value });
+        }
+        if self.check(&IDENTIFIER) && self.check_next(&COLON) {
+            let name = self.advance();
+            self.advance();
+            let value = self.assignment()?;
+            return Ok(Argument::Named { name, value });
+        }
+        let value = self.assignment()?;
+        Ok(Argument::Positional { value })
     }
 
     fn primary(&mut self) -> Result<Expr, ParseError> {
@@ -323,11 +1322,69 @@ impl Parser {
                 value: self.previous().literal.clone().unwrap(),
             });
         }
+        if self.match_token(&[SUPER]) {
+            let keyword = self.previous();
+            self.consume(DOT, "Expect '.' after 'super'.".to_string())?;
+            let method = self.consume(IDENTIFIER, "Expect superclass method name.".to_string())?;
+            return Ok(Expr::Super {
+                id: expr::next_id(),
+                keyword,
+                method,
+            });
+        }
+        if self.match_token(&[FUN]) {
+            let keyword = self.previous();
+            self.consume(LEFT_PAREN, "Expect '(' after 'fun'.".to_string())?;
+            return Ok(Expr::Function {
+                declaration: self.function_body(keyword, "function")?,
+            });
+        }
+        if self.match_token(&[THIS]) {
+            return Ok(Expr::This {
+                id: expr::next_id(),
+                keyword: self.previous(),
+            });
+        }
+        if self.match_token(&[INTERPOLATION]) {
+            return self.interpolation();
+        }
         if self.match_token(&[IDENTIFIER]) {
             return Ok(Expr::Variable {
+                id: expr::next_id(),
                 name: self.previous(),
             });
         }
+        if self.match_token(&[LEFT_BRACKET]) {
+            let mut elements = Vec::new();
+            if !self.check(&RIGHT_BRACKET) {
+                loop {
+                    elements.push(self.assignment()?);
+                    if !self.match_token(&[COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(RIGHT_BRACKET, "Expect ']' after list elements.".to_string())?;
+            return Ok(Expr::List { elements });
+        }
+        // 语句开头的 '{' 已经被 statement() 当作代码块，这里只会遇到表达式中的 map 字面量
+        if self.match_token(&[LEFT_BRACE]) {
+            let brace = self.previous();
+            let mut entries = Vec::new();
+            if !self.check(&RIGHT_BRACE) {
+                loop {
+                    let key = self.assignment()?;
+                    self.consume(COLON, "Expect ':' after map key.".to_string())?;
+                    let value = self.assignment()?;
+                    entries.push((key, value));
+                    if !self.match_token(&[COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(RIGHT_BRACE, "Expect '}' after map entries.".to_string())?;
+            return Ok(Expr::Map { brace, entries });
+        }
         if self.match_token(&[LEFT_PAREN]) {
             let expr = self.expression()?;
             self.consume(RIGHT_PAREN, "Expect ')' after expression.".to_string())?;
@@ -338,6 +1395,36 @@ impl Parser {
         Err(Self::error(self.peek(), "Expect expression.".to_string()))
     }
 
+    // "a ${b} c" 被扫描为 INTERPOLATION("a ") b INTERPOLATION_END(" c")，多个内嵌表达式依此类推
+    fn interpolation(&mut self) -> Result<Expr, ParseError> {
+        let token = self.previous();
+        let mut parts = Vec::new();
+        loop {
+            Self::push_string_part(&mut parts, self.previous());
+            parts.push(self.expression()?);
+            if self.match_token(&[INTERPOLATION]) {
+                continue;
+            }
+            let end = self.consume(
+                INTERPOLATION_END,
+                "Expect '}' after interpolated expression.".to_string(),
+            )?;
+            Self::push_string_part(&mut parts, end);
+            break;
+        }
+        Ok(Expr::Interpolation { token, parts })
+    }
+
+    fn push_string_part(parts: &mut Vec<Expr>, token: Token) {
+        if let Some(Literal::String(s)) = &token.literal {
+            if !s.is_empty() {
+                parts.push(Expr::Literal {
+                    value: Literal::String(s.clone()),
+                });
+            }
+        }
+    }
+
     fn match_token(&mut self, types: &[TokenType]) -> bool {
         for token_type in types {
             if self.check(token_type) {
@@ -369,6 +1456,13 @@ impl Parser {
         self.previous()
     }
 
+    fn check_next(&self, token_type: &TokenType) -> bool {
+        match self.tokens.get(self.current + 1) {
+            Some(token) => token.token_type == *token_type,
+            None => false,
+        }
+    }
+
     fn is_at_end(&self) -> bool {
         self.peek().token_type == EOF
     }
@@ -393,7 +1487,8 @@ impl Parser {
                 return;
             }
             match self.peek().token_type {
-                CLASS | FUN | VAR | FOR | IF | WHILE | PRINT | RETURN => return,
+                CLASS | TRAIT | ENUM | FUN | VAR | CONST | FOR | IF | WHILE | PRINT | RETURN
+                | IMPORT | THROW | TRY | MATCH | BREAK | CONTINUE => return,
                 _ => (),
             }
             self.advance();
@@ -0,0 +1,567 @@
+use crate::expr::{Argument, AssignPattern, Expr};
+use crate::interpreter::Interpreter;
+use crate::stmt::{CatchClause, ClassBody, Destructure, FunctionDecl, MatchArm, Pattern, Stmt};
+use crate::token::{Literal, Token};
+use crate::Lox;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+    Trait,
+}
+
+// 静态解析：在执行前为每个局部变量的使用记录它距离声明处隔了几层作用域
+pub(crate) struct Resolver<'a> {
+    interpreter: &'a mut Interpreter,
+    // 每层作用域中变量名到“是否已完成初始化”的映射，全局作用域不在其中
+    scopes: Vec<HashMap<String, bool>>,
+    // 与 scopes 一一对应，记录每层作用域中的常量及其声明所在的行
+    constants: Vec<HashMap<String, i32>>,
+    current_function: FunctionType,
+    current_class: ClassType,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn new(interpreter: &'a mut Interpreter) -> Self {
+        Resolver {
+            interpreter,
+            scopes: Vec::new(),
+            constants: Vec::new(),
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+        }
+    }
+
+    pub(crate) fn resolve(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
+        stmt.accept(self)
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) {
+        expr.accept(self)
+    }
+
+    fn resolve_function(&mut self, function: &FunctionDecl, function_type: FunctionType) {
+        let enclosing_function = self.current_function;
+        self.current_function = function_type;
+
+        self.begin_scope();
+        for param in &function.params {
+            if let Some(default) = &param.default {
+                self.resolve_expr(default);
+            }
+            self.declare(&param.name);
+            self.define(&param.name);
+        }
+        self.resolve(&function.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.constants.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+        self.constants.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        if scope.contains_key(&name.lexeme) {
+            Lox::error_at_token(
+                name.clone(),
+                "Already a variable with this name in this scope.".to_string(),
+            );
+        }
+        scope.insert(name.lexeme.clone(), false);
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), true);
+        }
+    }
+
+    // 模式中的 enum 名是对变量的引用，绑定的名字则在分支的作用域中声明
+    fn resolve_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Binding { name } => {
+                self.declare(name);
+                self.define(name);
+            }
+            Pattern::Variant {
+                id,
+                enum_name,
+                fields,
+                ..
+            } => {
+                self.resolve_local(*id, enum_name);
+                for field in fields.iter().flatten() {
+                    self.resolve_pattern(field);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn define_constant(&mut self, name: &Token) {
+        self.define(name);
+        if let Some(constants) = self.constants.last_mut() {
+            constants.insert(name.lexeme.clone(), name.line);
+        }
+    }
+
+    // 局部常量的赋值在这里报错；全局常量要到运行时才能确定
+    fn check_assignment(&self, name: &Token) {
+        for (scope, constants) in self.scopes.iter().zip(&self.constants).rev() {
+            if scope.contains_key(&name.lexeme) {
+                if let Some(line) = constants.get(&name.lexeme) {
+                    Lox::error_at_token(
+                        name.clone(),
+                        format!(
+                            "Can't assign to constant '{}' declared on line {}.",
+                            name.lexeme, line
+                        ),
+                    );
+                }
+                return;
+            }
+        }
+    }
+
+    // 从内向外查找变量，找不到则视为全局变量，不做记录
+    fn resolve_local(&mut self, id: usize, name: &Token) {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.contains_key(&name.lexeme) {
+                self.interpreter.resolve(id, depth);
+                return;
+            }
+        }
+    }
+
+    // 方法体外包一层作用域存放 this，对应 LoxFunction::bind 创建的环境
+    fn resolve_members(&mut self, body: &ClassBody) {
+        self.begin_scope();
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert("this".to_string(), true);
+        }
+        for method in &body.methods {
+            let function_type = if method.name.lexeme == "init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            self.resolve_function(method, function_type);
+        }
+        // 类方法中的 this 指向类本身，同样由 bind 提供
+        let accessors = body.getters.iter().chain(&body.setters);
+        for method in accessors.chain(&body.class_methods) {
+            self.resolve_function(method, FunctionType::Method);
+        }
+        self.end_scope();
+    }
+}
+
+impl crate::expr::Visitor<()> for Resolver<'_> {
+    fn visit_binary_expr(&mut self, left: &Expr, _operator: &Token, right: &Expr) {
+        self.resolve_expr(left);
+        self.resolve_expr(right);
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) {
+        self.resolve_expr(expression);
+    }
+
+    fn visit_literal_expr(&mut self, _value: &Literal) {}
+
+    fn visit_unary_expr(&mut self, _operator: &Token, right: &Expr) {
+        self.resolve_expr(right);
+    }
+
+    fn visit_variable_expr(&mut self, id: usize, name: &Token) {
+        if let Some(scope) = self.scopes.last() {
+            if scope.get(&name.lexeme) == Some(&false) {
+                Lox::error_at_token(
+                    name.clone(),
+                    "Can't read local variable in its own initializer.".to_string(),
+                );
+            }
+        }
+        self.resolve_local(id, name);
+    }
+
+    fn visit_assign_expr(&mut self, id: usize, name: &Token, value: &Expr) {
+        self.resolve_expr(value);
+        self.check_assignment(name);
+        self.resolve_local(id, name);
+    }
+
+    fn visit_logical_expr(&mut self, left: &Expr, _operator: &Token, right: &Expr) {
+        self.resolve_expr(left);
+        self.resolve_expr(right);
+    }
+
+    fn visit_call_expr(&mut self, callee: &Expr, _paren: &Token, arguments: &[Argument]) {
+        self.resolve_expr(callee);
+        for argument in arguments {
+            self.resolve_expr(argument.value());
+        }
+    }
+
+    fn visit_get_expr(&mut self, object: &Expr, _name: &Token) {
+        self.resolve_expr(object);
+    }
+
+    fn visit_set_expr(&mut self, object: &Expr, _name: &Token, value: &Expr) {
+        self.resolve_expr(value);
+        self.resolve_expr(object);
+    }
+
+    fn visit_this_expr(&mut self, id: usize, keyword: &Token) {
+        if self.current_class == ClassType::None {
+            Lox::error_at_token(
+                keyword.clone(),
+                "Can't use 'this' outside of a class.".to_string(),
+            );
+            return;
+        }
+        self.resolve_local(id, keyword);
+    }
+
+    fn visit_super_expr(&mut self, id: usize, keyword: &Token, _method: &Token) {
+        match self.current_class {
+            ClassType::None => Lox::error_at_token(
+                keyword.clone(),
+                "Can't use 'super' outside of a class.".to_string(),
+            ),
+            ClassType::Class => Lox::error_at_token(
+                keyword.clone(),
+                "Can't use 'super' in a class with no superclass.".to_string(),
+            ),
+            ClassType::Trait => {
+                Lox::error_at_token(keyword.clone(), "Can't use 'super' in a trait.".to_string())
+            }
+            ClassType::Subclass => {}
+        }
+        self.resolve_local(id, keyword);
+    }
+
+    fn visit_function_expr(&mut self, declaration: &Rc<FunctionDecl>) {
+        self.resolve_function(declaration, FunctionType::Function);
+    }
+
+    fn visit_list_expr(&mut self, elements: &[Expr]) {
+        for element in elements {
+            self.resolve_expr(element);
+        }
+    }
+
+    fn visit_index_expr(&mut self, object: &Expr, _bracket: &Token, index: &Expr) {
+        self.resolve_expr(object);
+        self.resolve_expr(index);
+    }
+
+    fn visit_index_set_expr(
+        &mut self,
+        object: &Expr,
+        _bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) {
+        self.resolve_expr(object);
+        self.resolve_expr(index);
+        self.resolve_expr(value);
+    }
+
+    fn visit_map_expr(&mut self, _brace: &Token, entries: &[(Expr, Expr)]) {
+        for (key, value) in entries {
+            self.resolve_expr(key);
+            self.resolve_expr(value);
+        }
+    }
+
+    fn visit_interpolation_expr(&mut self, _token: &Token, parts: &[Expr]) {
+        for part in parts {
+            self.resolve_expr(part);
+        }
+    }
+
+    fn visit_compound_assign_expr(&mut self, target: &Expr, _operator: &Token, value: &Expr) {
+        if let Expr::Variable { name, .. } = target {
+            self.check_assignment(name);
+        }
+        self.resolve_expr(target);
+        self.resolve_expr(value);
+    }
+
+    fn visit_increment_expr(&mut self, target: &Expr, _operator: &Token, _postfix: bool) {
+        if let Expr::Variable { name, .. } = target {
+            self.check_assignment(name);
+        }
+        self.resolve_expr(target);
+    }
+
+    fn visit_conditional_expr(&mut self, condition: &Expr, then_branch: &Expr, else_branch: &Expr) {
+        self.resolve_expr(condition);
+        self.resolve_expr(then_branch);
+        self.resolve_expr(else_branch);
+    }
+
+    fn visit_destructure_assign_expr(
+        &mut self,
+        pattern: &AssignPattern,
+        _equals: &Token,
+        value: &Expr,
+    ) {
+        self.resolve_expr(value);
+        for target in pattern.targets() {
+            if let Expr::Variable { name, .. } = target {
+                self.check_assignment(name);
+            }
+            self.resolve_expr(target);
+        }
+    }
+}
+
+impl crate::stmt::Visitor<()> for Resolver<'_> {
+    fn visit_expression_stmt(&mut self, expr: &Expr) {
+        self.resolve_expr(expr);
+    }
+
+    fn visit_print_stmt(&mut self, _keyword: &Token, expr: &Expr) {
+        self.resolve_expr(expr);
+    }
+
+    fn visit_var_stmt(&mut self, name: &Token, initializer: Option<&Expr>) {
+        self.declare(name);
+        if let Some(initializer) = initializer {
+            self.resolve_expr(initializer);
+        }
+        self.define(name);
+    }
+
+    fn visit_const_stmt(&mut self, name: &Token, initializer: &Expr) {
+        self.declare(name);
+        self.resolve_expr(initializer);
+        self.define_constant(name);
+    }
+
+    fn visit_destructure_stmt(&mut self, pattern: &Destructure, initializer: &Expr) {
+        let names = pattern.names();
+        for name in &names {
+            self.declare(name);
+        }
+        self.resolve_expr(initializer);
+        for name in names {
+            self.define(name);
+        }
+    }
+
+    fn visit_block_stmt(&mut self, statements: &[Stmt]) {
+        self.begin_scope();
+        self.resolve(statements);
+        self.end_scope();
+    }
+
+    fn visit_if_stmt(&mut self, condition: &Expr, then_branch: &Stmt, else_branch: Option<&Stmt>) {
+        self.resolve_expr(condition);
+        self.resolve_stmt(then_branch);
+        if let Some(else_branch) = else_branch {
+            self.resolve_stmt(else_branch);
+        }
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: Option<&Expr>,
+        _label: Option<&Token>,
+    ) {
+        self.resolve_expr(condition);
+        self.resolve_stmt(body);
+        if let Some(increment) = increment {
+            self.resolve_expr(increment);
+        }
+    }
+
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &Token,
+        _keyword: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+        _label: Option<&Token>,
+    ) {
+        self.resolve_expr(iterable);
+        // 循环变量单独占一层作用域，与运行时每次迭代新建的环境对应
+        self.begin_scope();
+        self.declare(name);
+        self.define(name);
+        self.resolve_stmt(body);
+        self.end_scope();
+    }
+
+    fn visit_enum_stmt(&mut self, name: &Token, _variants: &[(Token, Vec<Token>)]) {
+        self.declare(name);
+        self.define(name);
+    }
+
+    fn visit_match_stmt(&mut self, _keyword: &Token, subject: &Expr, arms: &[MatchArm]) {
+        self.resolve_expr(subject);
+        for arm in arms {
+            self.begin_scope();
+            self.resolve_pattern(&arm.pattern);
+            if let Some(guard) = &arm.guard {
+                self.resolve_expr(guard);
+            }
+            self.resolve_stmt(&arm.body);
+            self.end_scope();
+        }
+    }
+
+    fn visit_break_stmt(&mut self, _keyword: &Token, _label: Option<&Token>) {}
+
+    fn visit_continue_stmt(&mut self, _keyword: &Token, _label: Option<&Token>) {}
+
+    fn visit_import_stmt(&mut self, keyword: &Token, _path: &Token, _alias: Option<&Token>) {
+        // 导入的名字只有运行时才知道，所以只允许出现在全局作用域
+        if !self.scopes.is_empty() {
+            Lox::error_at_token(
+                keyword.clone(),
+                "Can only import at the top level.".to_string(),
+            );
+        }
+    }
+
+    fn visit_throw_stmt(&mut self, _keyword: &Token, value: &Expr) {
+        self.resolve_expr(value);
+    }
+
+    fn visit_try_stmt(
+        &mut self,
+        body: &[Stmt],
+        catch: Option<&CatchClause>,
+        finally: Option<&[Stmt]>,
+    ) {
+        self.visit_block_stmt(body);
+        if let Some(catch) = catch {
+            // 异常变量和 catch 块中的语句位于同一个作用域
+            self.begin_scope();
+            self.declare(&catch.name);
+            self.define(&catch.name);
+            self.resolve(&catch.body);
+            self.end_scope();
+        }
+        if let Some(finally) = finally {
+            self.visit_block_stmt(finally);
+        }
+    }
+
+    fn visit_function_stmt(&mut self, declaration: &Rc<FunctionDecl>) {
+        // 先定义函数名，函数体内才能递归引用自身
+        self.declare(&declaration.name);
+        self.define(&declaration.name);
+        self.resolve_function(declaration, FunctionType::Function);
+    }
+
+    fn visit_return_stmt(&mut self, keyword: &Token, value: Option<&Expr>) {
+        if self.current_function == FunctionType::None {
+            Lox::error_at_token(
+                keyword.clone(),
+                "Can't return from top-level code.".to_string(),
+            );
+        }
+        if let Some(value) = value {
+            if self.current_function == FunctionType::Initializer {
+                Lox::error_at_token(
+                    keyword.clone(),
+                    "Can't return a value from an initializer.".to_string(),
+                );
+            }
+            self.resolve_expr(value);
+        }
+    }
+
+    fn visit_class_stmt(
+        &mut self,
+        name: &Token,
+        superclass: Option<&Expr>,
+        traits: &[Expr],
+        body: &ClassBody,
+    ) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(name);
+        self.define(name);
+
+        for lox_trait in traits {
+            self.resolve_expr(lox_trait);
+        }
+        if let Some(superclass) = superclass {
+            if let Expr::Variable {
+                name: superclass_name,
+                ..
+            } = superclass
+            {
+                if superclass_name.lexeme == name.lexeme {
+                    Lox::error_at_token(
+                        superclass_name.clone(),
+                        "A class can't inherit from itself.".to_string(),
+                    );
+                }
+            }
+            self.current_class = ClassType::Subclass;
+            self.resolve_expr(superclass);
+
+            // super 所在的作用域包在 this 的作用域之外
+            self.begin_scope();
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.insert("super".to_string(), true);
+            }
+        }
+
+        self.resolve_members(body);
+
+        if superclass.is_some() {
+            self.end_scope();
+        }
+
+        self.current_class = enclosing_class;
+    }
+
+    fn visit_trait_stmt(&mut self, name: &Token, body: &ClassBody, _required: &[Token]) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Trait;
+
+        self.declare(name);
+        self.define(name);
+        self.resolve_members(body);
+
+        self.current_class = enclosing_class;
+    }
+}
@@ -1,12 +1,33 @@
 use crate::token::Token;
+use crate::value::Value;
 
 pub(crate) struct RuntimeError {
     pub(crate) token: Token,
     pub(crate) message: String,
+    // throw 语句抛出的值；解释器自身产生的错误为 None
+    pub(crate) value: Option<Value>,
 }
 
 impl RuntimeError {
     pub(crate) fn new(token: Token, message: String) -> RuntimeError {
-        RuntimeError { token, message }
+        RuntimeError {
+            token,
+            message,
+            value: None,
+        }
+    }
+
+    pub(crate) fn thrown(token: Token, value: Value) -> RuntimeError {
+        // 重新抛出捕获到的内置错误时沿用它原来的信息
+        let message = match &value {
+            Value::Instance(instance) => instance.borrow().field("message"),
+            _ => None,
+        }
+        .unwrap_or_else(|| Value::String(format!("Uncaught exception: {}", value)));
+        RuntimeError {
+            token,
+            message: message.to_string(),
+            value: Some(value),
+        }
     }
 }
@@ -10,21 +10,34 @@ lazy_static! {
     static ref KEYWORDS: HashMap<String, TokenType> = {
         [
             ("and", AND),
+            ("break", BREAK),
+            ("catch", CATCH),
             ("class", CLASS),
+            ("const", CONST),
+            ("continue", CONTINUE),
             ("else", ELSE),
+            ("enum", ENUM),
             ("false", FALSE),
+            ("finally", FINALLY),
             ("for", FOR), 
             ("fun", FUN),
             ("if", IF), 
+            ("import", IMPORT),
+            ("in", IN),
+            ("match", MATCH),
             ("nil", NIL),
             ("or", OR),
             ("print", PRINT),
             ("return", RETURN),
             ("super", SUPER),
             ("this", THIS), 
+            ("throw", THROW),
+            ("trait", TRAIT),
             ("true", TRUE), 
+            ("try", TRY),
             ("var", VAR), 
             ("while", WHILE),
+            ("with", WITH),
         ]
         .iter()
         .map(|&(k, v)| (String::from(k), v))
@@ -38,6 +51,8 @@ pub(crate) struct Scanner {
     start: i32,
     current: i32,
     line: i32,
+    // 每层尚未闭合的 "${" 内部还未匹配的 '{' 个数
+    interpolations: Vec<i32>,
 }
 
 impl Scanner {
@@ -48,6 +63,7 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
+            interpolations: Vec::new(),
         }
     }
 
@@ -56,6 +72,9 @@ impl Scanner {
             self.start = self.current;
             self.scan_token();
         }
+        if !self.interpolations.is_empty() {
+            Lox::error_at_line(self.line, "Unterminated string interpolation.".to_string());
+        }
 
         self.tokens.push(Token::new(EOF, String::from(""), None, self.line));
         self.tokens
@@ -70,20 +89,86 @@ impl Scanner {
         match c {
             '(' => self.add_token(LEFT_PAREN),
             ')' => self.add_token(RIGHT_PAREN),
-            '{' => self.add_token(LEFT_BRACE),
-            '}' => self.add_token(RIGHT_BRACE),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(LEFT_BRACE)
+            }
+            '}' => match self.interpolations.last_mut() {
+                // 内嵌表达式结束，接着扫描字符串的剩余部分
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string(INTERPOLATION_END);
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(RIGHT_BRACE)
+                }
+                None => self.add_token(RIGHT_BRACE),
+            },
+            '[' => self.add_token(LEFT_BRACKET),
+            ']' => self.add_token(RIGHT_BRACKET),
+            ':' => self.add_token(COLON),
             ',' => self.add_token(COMMA),
-            '.' => self.add_token(DOT),
-            '-' => self.add_token(MINUS),
-            '+' => self.add_token(PLUS),
+            '.' => {
+                let token_type = if !self.match_char('.') {
+                    DOT
+                } else if self.match_char('.') {
+                    DOT_DOT_DOT
+                } else if self.match_char('=') {
+                    DOT_DOT_EQUAL
+                } else {
+                    DOT_DOT
+                };
+                self.add_token(token_type);
+            }
+            '-' => {
+                let token_type = if self.match_char('-') {
+                    MINUS_MINUS
+                } else if self.match_char('=') {
+                    MINUS_EQUAL
+                } else {
+                    MINUS
+                };
+                self.add_token(token_type);
+            }
+            '+' => {
+                let token_type = if self.match_char('+') {
+                    PLUS_PLUS
+                } else if self.match_char('=') {
+                    PLUS_EQUAL
+                } else {
+                    PLUS
+                };
+                self.add_token(token_type);
+            }
             ';' => self.add_token(SEMICOLON),
-            '*' => self.add_token(STAR),
+            '?' => self.add_token(QUESTION),
+            '%' => self.add_token(PERCENT),
+            '~' => {
+                if self.match_char('/') {
+                    self.add_token(TILDE_SLASH);
+                } else {
+                    Lox::error_at_line(self.line, "Expect '/' after '~'.".to_string())
+                }
+            }
+            '*' => {
+                let token_type = if self.match_char('=') { STAR_EQUAL } else { STAR };
+                self.add_token(token_type);
+            }
             '!' => {
                 let token_type = if self.match_char('=') { BANG_EQUAL } else { BANG };
                 self.add_token(token_type);
             }
             '=' => {
-                let token_type = if self.match_char('=') { EQUAL_EQUAL } else { EQUAL };
+                let token_type = if self.match_char('=') {
+                    EQUAL_EQUAL
+                } else if self.match_char('>') {
+                    FAT_ARROW
+                } else {
+                    EQUAL
+                };
                 self.add_token(token_type);
             }
             '<' => {
@@ -99,13 +184,15 @@ impl Scanner {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                } else if self.match_char('=') {
+                    self.add_token(SLASH_EQUAL);
                 } else {
                     self.add_token(SLASH);
                 }
             }
             ' ' | '\r' | '\t' => {}
             '\n' => self.line += 1,
-            '"' => self.string(),
+            '"' => self.string(STRING),
             c if Scanner::is_digit(c) => self.number(),
             c if Scanner::is_alpha(c) => self.identifier(),
             _ => {
@@ -127,8 +214,18 @@ impl Scanner {
             _ => self.add_token(token_type),
         }
     }
-    fn string(&mut self) {
+    // 从开头的 '"' 或内嵌表达式结尾的 '}' 之后扫描字符串，遇到 "${" 时先产生一段 INTERPOLATION，
+    // 否则直到 '"' 产生一个 end_type 类型的 token
+    fn string(&mut self, end_type: TokenType) {
         while self.peek() != '"' && !self.is_at_end() {
+            if self.peek() == '$' && self.peek_next() == '{' {
+                let value = self.source[self.start as usize + 1..self.current as usize].to_string();
+                self.advance();
+                self.advance();
+                self.interpolations.push(0);
+                self.add_token_with_literal(INTERPOLATION, Some(Literal::String(value)));
+                return;
+            }
             if self.peek() == '\n' {
                 self.line += 1;
             }
@@ -143,7 +240,7 @@ impl Scanner {
         self.advance();
 
         let value = &self.source[self.start as usize + 1..self.current as usize - 1];
-        self.add_token_with_literal(STRING, Some(Literal::String(value.to_string())));
+        self.add_token_with_literal(end_type, Some(Literal::String(value.to_string())));
     }
 
     fn number(&mut self) {
@@ -151,7 +248,10 @@ impl Scanner {
             self.advance();
         }
 
+        // 没有小数部分的字面量是整数
+        let mut is_float = false;
         if self.peek() == '.' && Scanner::is_digit(self.peek_next()) {
+            is_float = true;
             self.advance();
             while Scanner::is_digit(self.peek()) {
                 self.advance();
@@ -159,7 +259,14 @@ impl Scanner {
         }
 
         let value = &self.source[self.start as usize..self.current as usize];
-        self.add_token_with_literal(NUMBER, Some(Literal::Number(value.parse().unwrap())));
+        if is_float {
+            self.add_token_with_literal(NUMBER, Some(Literal::Number(value.parse().unwrap())));
+            return;
+        }
+        match value.parse() {
+            Ok(i) => self.add_token_with_literal(NUMBER, Some(Literal::Int(i))),
+            Err(_) => Lox::error_at_line(self.line, "Integer literal is too large.".to_string()),
+        }
     }
 
     // 判断当前字符是否为expected，如果是，current指针后移一位
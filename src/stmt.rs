@@ -1,31 +1,83 @@
 use crate::expr::Expr;
-use crate::token::Token;
+use crate::token::{Literal, Token};
+use crate::token_type::TokenType;
+use std::rc::Rc;
 
 pub(crate) trait Visitor<R> {
     fn visit_expression_stmt(&mut self, expr: &Expr) -> R;
-    fn visit_print_stmt(&mut self, expr: &Expr) -> R;
+    fn visit_print_stmt(&mut self, keyword: &Token, expr: &Expr) -> R;
     fn visit_var_stmt(&mut self, name: &Token, initializer: Option<&Expr>) -> R;
-    fn visit_block_stmt(&mut self, statements: &Vec<Stmt>) -> R;
+    fn visit_const_stmt(&mut self, name: &Token, initializer: &Expr) -> R;
+    fn visit_destructure_stmt(&mut self, pattern: &Destructure, initializer: &Expr) -> R;
+    fn visit_block_stmt(&mut self, statements: &[Stmt]) -> R;
     fn visit_if_stmt(
         &mut self,
         condition: &Expr,
         then_branch: &Stmt,
         else_branch: Option<&Stmt>,
     ) -> R;
-    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> R;
+    fn visit_while_stmt(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: Option<&Expr>,
+        label: Option<&Token>,
+    ) -> R;
+    fn visit_for_in_stmt(
+        &mut self,
+        name: &Token,
+        keyword: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+        label: Option<&Token>,
+    ) -> R;
+    fn visit_enum_stmt(&mut self, name: &Token, variants: &[(Token, Vec<Token>)]) -> R;
+    fn visit_match_stmt(&mut self, keyword: &Token, subject: &Expr, arms: &[MatchArm]) -> R;
+    fn visit_break_stmt(&mut self, keyword: &Token, label: Option<&Token>) -> R;
+    fn visit_continue_stmt(&mut self, keyword: &Token, label: Option<&Token>) -> R;
+    fn visit_import_stmt(&mut self, keyword: &Token, path: &Token, alias: Option<&Token>) -> R;
+    fn visit_throw_stmt(&mut self, keyword: &Token, value: &Expr) -> R;
+    fn visit_try_stmt(
+        &mut self,
+        body: &[Stmt],
+        catch: Option<&CatchClause>,
+        finally: Option<&[Stmt]>,
+    ) -> R;
+    fn visit_function_stmt(&mut self, declaration: &Rc<FunctionDecl>) -> R;
+    fn visit_return_stmt(&mut self, keyword: &Token, value: Option<&Expr>) -> R;
+    fn visit_class_stmt(
+        &mut self,
+        name: &Token,
+        superclass: Option<&Expr>,
+        traits: &[Expr],
+        body: &ClassBody,
+    ) -> R;
+    fn visit_trait_stmt(&mut self, name: &Token, body: &ClassBody, required: &[Token]) -> R;
 }
 
+#[derive(Debug)]
 pub(crate) enum Stmt {
     Expression {
         expression: Box<Expr>,
     },
     Print {
+        keyword: Token,
         expression: Box<Expr>,
     },
     Var {
         name: Token,
         initializer: Option<Box<Expr>>,
     },
+    // 常量必须初始化，之后不能再赋值
+    Const {
+        name: Token,
+        initializer: Box<Expr>,
+    },
+    // var [a, b, ...rest] = e; 或 var {x, y} = e;
+    Destructure {
+        pattern: Destructure,
+        initializer: Box<Expr>,
+    },
     Block {
         statements: Vec<Stmt>,
     },
@@ -37,22 +89,276 @@ pub(crate) enum Stmt {
     While {
         condition: Box<Expr>,
         body: Box<Stmt>,
+        // 由 for 循环脱糖而来时的递增子句，continue 之后也要执行
+        increment: Option<Box<Expr>>,
+        label: Option<Token>,
+    },
+    // for (name in iterable) body，每次迭代都为 name 新建一个环境
+    ForIn {
+        name: Token,
+        keyword: Token,
+        iterable: Box<Expr>,
+        body: Box<Stmt>,
+        label: Option<Token>,
+    },
+    // 每个变体带有若干字段名，没有字段的变体本身就是一个值
+    Enum {
+        name: Token,
+        variants: Vec<(Token, Vec<Token>)>,
+    },
+    // 自上而下尝试各个分支，执行第一个匹配的分支
+    Match {
+        keyword: Token,
+        subject: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+    Break {
+        keyword: Token,
+        label: Option<Token>,
+    },
+    Continue {
+        keyword: Token,
+        label: Option<Token>,
+    },
+    // 函数声明由 Rc 共享，运行时的 LoxFunction 在语法树被丢弃后仍然持有它
+    Function {
+        declaration: Rc<FunctionDecl>,
+    },
+    Return {
+        keyword: Token,
+        value: Option<Box<Expr>>,
+    },
+    // import "path"; 把模块的顶层声明引入当前作用域，import "path" as name; 则绑定为一个命名空间
+    Import {
+        keyword: Token,
+        path: Token,
+        alias: Option<Token>,
+    },
+    Throw {
+        keyword: Token,
+        value: Box<Expr>,
+    },
+    // catch 与 finally 至少有一个
+    Try {
+        body: Vec<Stmt>,
+        catch: Option<CatchClause>,
+        finally: Option<Vec<Stmt>>,
+    },
+    Class {
+        name: Token,
+        // 总是 Expr::Variable
+        superclass: Option<Box<Expr>>,
+        // 混入的 trait，总是 Expr::Variable
+        traits: Vec<Expr>,
+        body: ClassBody,
+    },
+    // required 是只有签名、必须由使用它的类实现的方法
+    Trait {
+        name: Token,
+        body: ClassBody,
+        required: Vec<Token>,
     },
 }
 
+// getter 没有参数列表，访问属性时即被调用；setter 在给同名属性赋值时被调用；
+// 类方法通过类对象本身调用，其中的 this 指向类
+#[derive(Debug, Default)]
+pub(crate) struct ClassBody {
+    pub(crate) methods: Vec<Rc<FunctionDecl>>,
+    pub(crate) getters: Vec<Rc<FunctionDecl>>,
+    pub(crate) setters: Vec<Rc<FunctionDecl>>,
+    pub(crate) class_methods: Vec<Rc<FunctionDecl>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CatchClause {
+    pub(crate) name: Token,
+    pub(crate) body: Vec<Stmt>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Destructure {
+    // 没有 rest 时元素个数必须与列表长度相同
+    List {
+        bracket: Token,
+        names: Vec<Token>,
+        rest: Option<Token>,
+    },
+    // 按名字从 map、实例或模块中取值
+    Object {
+        brace: Token,
+        names: Vec<Token>,
+    },
+}
+
+impl Destructure {
+    // 声明的全部变量，按出现顺序
+    pub(crate) fn names(&self) -> Vec<&Token> {
+        match self {
+            Destructure::List { names, rest, .. } => names.iter().chain(rest).collect(),
+            Destructure::Object { names, .. } => names.iter().collect(),
+        }
+    }
+}
+
+// 每个分支有自己的作用域，绑定模式引入的变量只在守卫和分支体中可见
+#[derive(Debug)]
+pub(crate) struct MatchArm {
+    pub(crate) pattern: Pattern,
+    pub(crate) guard: Option<Expr>,
+    pub(crate) body: Stmt,
+}
+
+#[derive(Debug)]
+pub(crate) enum Pattern {
+    Literal {
+        token: Token,
+        value: Literal,
+    },
+    // _
+    Wildcard {
+        token: Token,
+    },
+    Binding {
+        name: Token,
+    },
+    // start..end 不含 end，start..=end 包含 end
+    Range {
+        token: Token,
+        start: Literal,
+        end: Literal,
+        inclusive: bool,
+    },
+    // Shape.Circle(r) 或 Shape.Empty；id 用于解析 enum 名所在的作用域
+    Variant {
+        id: usize,
+        enum_name: Token,
+        variant: Token,
+        fields: Option<Vec<Pattern>>,
+    },
+}
+
+impl Pattern {
+    // 模式的第一个 token，用于报告位置
+    pub(crate) fn token(&self) -> &Token {
+        match self {
+            Pattern::Literal { token, .. } => token,
+            Pattern::Wildcard { token } => token,
+            Pattern::Binding { name } => name,
+            Pattern::Range { token, .. } => token,
+            Pattern::Variant { enum_name, .. } => enum_name,
+        }
+    }
+
+    // 不带守卫时总能匹配的模式，其后的分支都不可达
+    pub(crate) fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard { .. } | Pattern::Binding { .. })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct FunctionDecl {
+    // 匿名函数没有名字，这里存放的是 fun 关键字本身
+    pub(crate) name: Token,
+    pub(crate) params: Vec<Param>,
+    pub(crate) body: Vec<Stmt>,
+}
+
+// 默认值在调用时求值，可以引用前面的参数；rest 参数只能是最后一个，收集多余的位置参数
+#[derive(Debug)]
+pub(crate) struct Param {
+    pub(crate) name: Token,
+    pub(crate) default: Option<Expr>,
+    pub(crate) rest: bool,
+}
+
+impl FunctionDecl {
+    pub(crate) fn is_anonymous(&self) -> bool {
+        self.name.token_type == TokenType::FUN
+    }
+}
+
 impl Stmt {
+    // 这条语句在所在作用域中声明的名字，用于确定模块导出的顶层声明
+    pub(crate) fn declared_names(&self) -> Vec<&Token> {
+        match self {
+            Stmt::Var { name, .. }
+            | Stmt::Const { name, .. }
+            | Stmt::Class { name, .. }
+            | Stmt::Trait { name, .. }
+            | Stmt::Enum { name, .. } => vec![name],
+            Stmt::Function { declaration } => vec![&declaration.name],
+            Stmt::Destructure { pattern, .. } => pattern.names(),
+            _ => Vec::new(),
+        }
+    }
+
     pub(crate) fn accept<R>(&self, visitor: &mut impl Visitor<R>) -> R {
         match self {
-            Stmt::Print { expression } => visitor.visit_print_stmt(expression),
+            Stmt::Print {
+                keyword,
+                expression,
+            } => visitor.visit_print_stmt(keyword, expression),
             Stmt::Expression { expression } => visitor.visit_expression_stmt(expression),
             Stmt::Var { name, initializer } => visitor.visit_var_stmt(name, initializer.as_deref()),
+            Stmt::Const { name, initializer } => visitor.visit_const_stmt(name, initializer),
+            Stmt::Destructure {
+                pattern,
+                initializer,
+            } => visitor.visit_destructure_stmt(pattern, initializer),
             Stmt::Block { statements } => visitor.visit_block_stmt(statements),
             Stmt::If {
                 condition,
                 then_branch,
                 else_branch,
             } => visitor.visit_if_stmt(condition, then_branch, else_branch.as_deref()),
-            Stmt::While { condition, body } => visitor.visit_while_stmt(condition, body),
+            Stmt::While {
+                condition,
+                body,
+                increment,
+                label,
+            } => visitor.visit_while_stmt(condition, body, increment.as_deref(), label.as_ref()),
+            Stmt::ForIn {
+                name,
+                keyword,
+                iterable,
+                body,
+                label,
+            } => visitor.visit_for_in_stmt(name, keyword, iterable, body, label.as_ref()),
+            Stmt::Enum { name, variants } => visitor.visit_enum_stmt(name, variants),
+            Stmt::Match {
+                keyword,
+                subject,
+                arms,
+            } => visitor.visit_match_stmt(keyword, subject, arms),
+            Stmt::Break { keyword, label } => visitor.visit_break_stmt(keyword, label.as_ref()),
+            Stmt::Continue { keyword, label } => {
+                visitor.visit_continue_stmt(keyword, label.as_ref())
+            }
+            Stmt::Function { declaration } => visitor.visit_function_stmt(declaration),
+            Stmt::Return { keyword, value } => visitor.visit_return_stmt(keyword, value.as_deref()),
+            Stmt::Import {
+                keyword,
+                path,
+                alias,
+            } => visitor.visit_import_stmt(keyword, path, alias.as_ref()),
+            Stmt::Throw { keyword, value } => visitor.visit_throw_stmt(keyword, value),
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+            } => visitor.visit_try_stmt(body, catch.as_ref(), finally.as_deref()),
+            Stmt::Class {
+                name,
+                superclass,
+                traits,
+                body,
+            } => visitor.visit_class_stmt(name, superclass.as_deref(), traits, body),
+            Stmt::Trait {
+                name,
+                body,
+                required,
+            } => visitor.visit_trait_stmt(name, body, required),
         }
     }
 }
@@ -23,6 +23,7 @@ impl Token {
 #[derive(Debug,Clone)]
 pub(crate) enum Literal {
     String(String),
+    Int(i64),
     Number(f64),
     Bool(bool),
     Nil,
@@ -32,6 +33,7 @@ impl Display for Literal{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Literal::String(s) => write!(f, "{}", s),
+            Literal::Int(i) => write!(f, "{}", i),
             Literal::Number(n) => write!(f, "{}", n),
             Literal::Bool(b) => write!(f, "{}", b),
             Literal::Nil => write!(f, "nil"),
@@ -3,21 +3,28 @@
 #[allow(clippy::upper_case_acronyms)]
 pub(crate) enum TokenType{
     // Single-character tokens.
-    LEFT_PAREN, RIGHT_PAREN, LEFT_BRACE, RIGHT_BRACE,
-    COMMA, DOT, MINUS, PLUS, SEMICOLON, SLASH, STAR,
+    LEFT_PAREN, RIGHT_PAREN, LEFT_BRACE, RIGHT_BRACE, LEFT_BRACKET, RIGHT_BRACKET,
+    COLON, COMMA, DOT, MINUS, PERCENT, PLUS, QUESTION, SEMICOLON, SLASH, STAR,
 
     // One or two character tokens.
     BANG, BANG_EQUAL,
-    EQUAL, EQUAL_EQUAL,
+    DOT_DOT, DOT_DOT_DOT, DOT_DOT_EQUAL,
+    MINUS_EQUAL, MINUS_MINUS, PLUS_EQUAL, PLUS_PLUS,
+    SLASH_EQUAL, STAR_EQUAL,
+    // "//" 已经是注释，整数除法写作 "~/"
+    TILDE_SLASH,
+    EQUAL, EQUAL_EQUAL, FAT_ARROW,
     GREATER, GREATER_EQUAL,
     LESS, LESS_EQUAL,
 
     // Literals.
-    IDENTIFIER, STRING, NUMBER,
+    // INTERPOLATION 是字符串中 "${" 之前的一段，其后紧跟内嵌表达式的 token；
+    // INTERPOLATION_END 是最后一个内嵌表达式之后直到 '"' 的一段
+    IDENTIFIER, STRING, INTERPOLATION, INTERPOLATION_END, NUMBER,
 
     // Keywords.
-    AND, CLASS, ELSE, FALSE, FUN, FOR, IF, NIL, OR,
-    PRINT, RETURN, SUPER, THIS, TRUE, VAR, WHILE,
+    AND, BREAK, CATCH, CLASS, CONST, CONTINUE, ELSE, ENUM, FALSE, FINALLY, FUN, FOR, IF, IMPORT, IN, MATCH, NIL, OR,
+    PRINT, RETURN, SUPER, THIS, THROW, TRAIT, TRUE, TRY, VAR, WHILE, WITH,
 
     EOF
 }
\ No newline at end of file
@@ -0,0 +1,17 @@
+use crate::runtime_error::RuntimeError;
+use crate::value::Value;
+
+// 语句执行时中断正常控制流的原因：运行时错误、return 语句携带的返回值，
+// 或者 break/continue（带上目标循环的标签，没有标签时作用于最内层循环）
+pub(crate) enum Unwind {
+    Error(RuntimeError),
+    Return(Value),
+    Break(Option<String>),
+    Continue(Option<String>),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
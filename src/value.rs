@@ -1,80 +1,257 @@
+use crate::lox_callable::LoxCallable;
+use crate::lox_class::LoxClass;
+use crate::lox_enum::{EnumVariant, LoxEnum};
+use crate::lox_instance::LoxInstance;
+use crate::lox_map::LoxMap;
+use crate::lox_module::LoxModule;
+use crate::lox_trait::LoxTrait;
+use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::fmt::Display;
+use std::rc::Rc;
 
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug)]
 pub(crate) enum Value {
+    Int(i64),
     Number(f64),
     Boolean(bool),
     String(String),
+    Callable(Rc<dyn LoxCallable>),
+    Class(Rc<LoxClass>),
+    Trait(Rc<LoxTrait>),
+    Instance(Rc<RefCell<LoxInstance>>),
+    // 列表是引用语义，赋值和传参共享同一个底层 Vec
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<LoxMap>>),
+    Module(Rc<LoxModule>),
+    Enum(Rc<LoxEnum>),
+    Variant(Rc<EnumVariant>),
     Nil,
 }
 
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Value::Int(i) => write!(f, "{}", i),
+            // 值为整数的浮点数也带上小数部分，与整数区分开
+            Value::Number(n) if n.is_finite() && n.fract() == 0.0 => write!(f, "{}.0", n),
             Value::Number(n) => write!(f, "{}", n),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::String(s) => write!(f, "{}", s),
+            Value::Callable(c) => write!(f, "{}", c),
+            Value::Class(c) => write!(f, "{}", c),
+            Value::Trait(t) => write!(f, "{}", t),
+            Value::Instance(i) => write!(f, "{}", i.borrow()),
+            Value::List(_) | Value::Map(_) | Value::Variant(_) => {
+                self.fmt_nested(f, &mut Vec::new())
+            }
+            Value::Module(module) => write!(f, "{}", module),
+            Value::Enum(lox_enum) => write!(f, "{}", lox_enum),
             Value::Nil => write!(f, "nil"),
         }
     }
 }
 
-impl std::ops::Neg for Value {
-    type Output = Self;
-
-    fn neg(self) -> Self::Output {
+impl Value {
+    // 列表和 map 是引用语义，可能直接或间接包含自身。enclosing 记录正在输出的容器，
+    // 再次遇到其中之一时输出 [...] 或 {...}。enum 变体的字段也可能引用外层容器
+    fn fmt_nested(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        enclosing: &mut Vec<*const ()>,
+    ) -> std::fmt::Result {
         match self {
-            Value::Number(n) => Value::Number(-n),
-            _ => panic!("Unary negation is only defined for numbers"),
+            Value::List(elements) => {
+                let address = Rc::as_ptr(elements) as *const ();
+                if enclosing.contains(&address) {
+                    return write!(f, "[...]");
+                }
+                enclosing.push(address);
+                write!(f, "[")?;
+                for (i, element) in elements.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.fmt_nested(f, enclosing)?;
+                }
+                enclosing.pop();
+                write!(f, "]")
+            }
+            Value::Map(map) => {
+                let address = Rc::as_ptr(map) as *const ();
+                if enclosing.contains(&address) {
+                    return write!(f, "{{...}}");
+                }
+                enclosing.push(address);
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.borrow().entries().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", key)?;
+                    value.fmt_nested(f, enclosing)?;
+                }
+                enclosing.pop();
+                write!(f, "}}")
+            }
+            Value::Variant(variant) => {
+                write!(f, "{}.{}", variant.lox_enum.name, variant.name)?;
+                if variant.payload.is_empty() {
+                    return Ok(());
+                }
+                write!(f, "(")?;
+                for (i, value) in variant.payload.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.fmt_nested(f, enclosing)?;
+                }
+                write!(f, ")")
+            }
+            _ => write!(f, "{}", self),
         }
     }
 }
 
-impl std::ops::Add for Value {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self::Output {
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Value::Number(l), Value::Number(r)) => Value::Number(l + r),
-            (Value::String(mut l), Value::String(r)) => {
-                l.push_str(&r);
-                Value::String(l)
+            (Value::Int(l), Value::Int(r)) => l == r,
+            // 整数与浮点数按数值比较，1 == 1.0
+            (Value::Int(_) | Value::Number(_), Value::Int(_) | Value::Number(_)) => {
+                self.as_f64() == other.as_f64()
             }
-            _ => panic!("Addition is only defined for two numbers or two strings"),
+            (Value::Boolean(l), Value::Boolean(r)) => l == r,
+            (Value::String(l), Value::String(r)) => l == r,
+            (Value::Callable(l), Value::Callable(r)) => Rc::ptr_eq(l, r),
+            (Value::Class(l), Value::Class(r)) => Rc::ptr_eq(l, r),
+            (Value::Trait(l), Value::Trait(r)) => Rc::ptr_eq(l, r),
+            (Value::Instance(l), Value::Instance(r)) => Rc::ptr_eq(l, r),
+            (Value::List(l), Value::List(r)) => Rc::ptr_eq(l, r),
+            (Value::Map(l), Value::Map(r)) => Rc::ptr_eq(l, r),
+            (Value::Module(l), Value::Module(r)) => Rc::ptr_eq(l, r),
+            (Value::Enum(l), Value::Enum(r)) => Rc::ptr_eq(l, r),
+            (Value::Variant(l), Value::Variant(r)) => l == r,
+            (Value::Nil, Value::Nil) => true,
+            _ => false,
         }
     }
 }
 
-impl std::ops::Sub for Value {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self::Output {
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
-            (Value::Number(l), Value::Number(r)) => Value::Number(l - r),
-            _ => panic!("Subtraction is only defined for two numbers"),
+            (Value::Int(l), Value::Int(r)) => l.partial_cmp(r),
+            (Value::Int(_) | Value::Number(_), Value::Int(_) | Value::Number(_)) => {
+                self.as_f64().partial_cmp(&other.as_f64())
+            }
+            (Value::Boolean(l), Value::Boolean(r)) => l.partial_cmp(r),
+            (Value::String(l), Value::String(r)) => l.partial_cmp(r),
+            _ => None,
         }
     }
 }
 
-impl std::ops::Mul for Value {
-    type Output = Self;
+// 数值运算的提升规则：两个整数得到整数，溢出时报错；只要有一个操作数是浮点数，
+// 另一个整数就先转换成浮点数再计算。'/' 总是浮点除法，'~/' 是向下取整的除法，
+// '%' 与之配套，结果与除数同号，满足 a == (a ~/ b) * b + a % b
+#[derive(Clone, Copy)]
+pub(crate) enum Arithmetic {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    FloorDivide,
+    Remainder,
+}
 
-    fn mul(self, other: Self) -> Self::Output {
-        match (self, other) {
-            (Value::Number(l), Value::Number(r)) => Value::Number(l * r),
-            _ => panic!("Multiplication is only defined for two numbers"),
+impl Value {
+    pub(crate) fn is_number(&self) -> bool {
+        matches!(self, Value::Int(_) | Value::Number(_))
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Number(n) => Some(*n),
+            _ => None,
         }
     }
-}
 
-impl std::ops::Div for Value {
-    type Output = Self;
+    // 整数，或者恰好等于某个 i64 的浮点数
+    pub(crate) fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            Value::Number(n)
+                if n.fract() == 0.0 && *n >= -(2f64.powi(63)) && *n < 2f64.powi(63) =>
+            {
+                Some(*n as i64)
+            }
+            _ => None,
+        }
+    }
 
-    fn div(self, other: Self) -> Self::Output {
-        match (self, other) {
-            (Value::Number(l), Value::Number(r)) => Value::Number(l / r),
-            _ => panic!("Division is only defined for two numbers"),
+    pub(crate) fn negate(&self) -> Result<Value, String> {
+        match self {
+            Value::Int(i) => i
+                .checked_neg()
+                .map(Value::Int)
+                .ok_or_else(|| "Integer overflow.".to_string()),
+            Value::Number(n) => Ok(Value::Number(-n)),
+            _ => Err("Operand must be a number.".to_string()),
+        }
+    }
+
+    pub(crate) fn arithmetic(
+        operation: Arithmetic,
+        left: &Value,
+        right: &Value,
+    ) -> Result<Value, String> {
+        if let (Value::Int(l), Value::Int(r)) = (left, right) {
+            return Self::int_arithmetic(operation, *l, *r);
+        }
+        let (Some(l), Some(r)) = (left.as_f64(), right.as_f64()) else {
+            return Err("Operands must be numbers.".to_string());
+        };
+        let result = match operation {
+            Arithmetic::Add => l + r,
+            Arithmetic::Subtract => l - r,
+            Arithmetic::Multiply => l * r,
+            Arithmetic::Divide => l / r,
+            Arithmetic::FloorDivide => (l / r).floor(),
+            Arithmetic::Remainder => l - r * (l / r).floor(),
+        };
+        Ok(Value::Number(result))
+    }
+
+    fn int_arithmetic(operation: Arithmetic, l: i64, r: i64) -> Result<Value, String> {
+        if r == 0 && matches!(operation, Arithmetic::FloorDivide | Arithmetic::Remainder) {
+            return Err("Integer division by zero.".to_string());
         }
+        let result = match operation {
+            Arithmetic::Add => l.checked_add(r),
+            Arithmetic::Subtract => l.checked_sub(r),
+            Arithmetic::Multiply => l.checked_mul(r),
+            Arithmetic::Divide => return Ok(Value::Number(l as f64 / r as f64)),
+            Arithmetic::FloorDivide => l.checked_div(r).map(|q| {
+                // 截断除法的商在不能整除且异号时比向下取整的商大 1
+                if l % r != 0 && (l < 0) != (r < 0) {
+                    q - 1
+                } else {
+                    q
+                }
+            }),
+            Arithmetic::Remainder => l.checked_rem(r).map(|m| {
+                if m != 0 && (m < 0) != (r < 0) {
+                    m + r
+                } else {
+                    m
+                }
+            }),
+        };
+        result
+            .map(Value::Int)
+            .ok_or_else(|| "Integer overflow.".to_string())
     }
 }
 
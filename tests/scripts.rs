@@ -0,0 +1,83 @@
+use std::path::Path;
+use std::process::Command;
+
+// 运行 tests/lox 下的脚本，把标准输出、标准错误和退出码与同名的 .out、.err 文件
+// 以及给定的退出码比较；缺少的 .out 或 .err 文件表示对应的输出应为空
+fn run(name: &str, code: i32) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox");
+    let output = Command::new(env!("CARGO_BIN_EXE_lox1"))
+        .arg(dir.join(format!("{}.lox", name)))
+        .output()
+        .expect("Failed to run the interpreter.");
+    let expected = |extension: &str| {
+        std::fs::read_to_string(dir.join(format!("{}.{}", name, extension))).unwrap_or_default()
+    };
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected("out"));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), expected("err"));
+    assert_eq!(output.status.code(), Some(code));
+}
+
+#[test]
+fn closures() {
+    run("closures", 0);
+}
+
+#[test]
+fn loops() {
+    run("loops", 0);
+}
+
+#[test]
+fn destructuring() {
+    run("destructuring", 0);
+}
+
+#[test]
+fn printing() {
+    run("printing", 0);
+}
+
+#[test]
+fn match_arms() {
+    run("match_arms", 0);
+}
+
+#[test]
+fn constants() {
+    run("constants", 70);
+}
+
+#[test]
+fn constant_import() {
+    run("constant_import", 70);
+}
+
+#[test]
+fn parse_recovery() {
+    run("parse_recovery", 65);
+}
+
+#[test]
+fn operators() {
+    run("operators", 0);
+}
+
+#[test]
+fn classes() {
+    run("classes", 0);
+}
+
+#[test]
+fn class_member_errors() {
+    run("class_member_errors", 65);
+}
+
+#[test]
+fn traits() {
+    run("traits", 0);
+}
+
+#[test]
+fn recursion() {
+    run("recursion", 70);
+}